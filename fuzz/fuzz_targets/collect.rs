@@ -0,0 +1,82 @@
+//! Feeds arbitrary response bodies to `collect` with the processing options picked
+//! by the first byte of the input.
+//!
+//! Run with `cargo fuzz run collect` from the repository root.
+
+#![no_main]
+
+use config::{ConfigError, Source, Value};
+use config_vault::transport::{TransportRequest, TransportResponse, VaultTransport};
+use config_vault::{EngineKind, KeyCase, TextPolicy, VaultSource};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug)]
+struct FixedBody(Vec<u8>);
+
+impl VaultTransport for FixedBody {
+    fn send(&self, _: &TransportRequest) -> Result<TransportResponse, ConfigError> {
+        Ok(TransportResponse::new(200, self.0.clone()))
+    }
+}
+
+fuzz_target!(|input: &[u8]| {
+    let Some((&options, body)) = input.split_first() else {
+        return;
+    };
+    let option = |bit: u8| options & (1 << bit) != 0;
+
+    let mut source = VaultSource::builder()
+        .addr("http://vault.test:8200")
+        .token("hvs.EXAMPLE_TOKEN")
+        .mount("secret")
+        .path("app")
+        .engine(if option(0) {
+            EngineKind::Kv1
+        } else {
+            EngineKind::Kv2
+        })
+        .transport(Box::new(FixedBody(body.to_vec())))
+        .build()
+        .unwrap();
+
+    let strict = option(1);
+    source.set_text_policy(if strict {
+        TextPolicy::Error
+    } else {
+        TextPolicy::Base64
+    });
+    if option(2) {
+        source.set_flatten_separator(Some(".".into()));
+    }
+    if option(3) {
+        source.set_interpolation(true);
+        source.add_required_key("url");
+    }
+    if option(4) {
+        source.set_key_case(KeyCase::Kebab);
+        source.add_key_rename("user", "username");
+    }
+    if option(5) {
+        source.set_json_key(Some("blob".into()));
+    }
+    if option(6) {
+        source.set_nested_at(Some("app".into()));
+        source.set_expiry_keys(true);
+        source.set_max_value_size(64);
+    }
+    if option(7) {
+        source.add_pem_bundle("bundle", vec!["cert".to_string(), "ca".to_string()]);
+        source.add_computed_key("count", |values| Ok(Value::from(values.len() as i64)));
+    }
+
+    let result = source.collect();
+
+    let valid_json = match std::str::from_utf8(body) {
+        Ok(text) => serde_json::from_str::<serde_json::Value>(text).is_ok(),
+        Err(_) if strict => false,
+        Err(_) => serde_json::from_str::<serde_json::Value>(&String::from_utf8_lossy(body)).is_ok(),
+    };
+    if !valid_json {
+        assert!(result.is_err(), "a malformed body loaded: {:?}", result);
+    }
+});
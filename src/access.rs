@@ -0,0 +1,159 @@
+//! Verification of the token's policy grants against an application manifest.
+//!
+//! Applications declare the Vault paths and capabilities they need in an
+//! [`AccessManifest`], typically deserialized from a deployment file. [`verify_access`]
+//! asks Vault which capabilities the configured token has on each path and reports
+//! the exact grants that are missing.
+
+use std::fmt;
+
+#[cfg(feature = "blocking")]
+use config::ConfigError;
+use serde::Deserialize;
+#[cfg(feature = "blocking")]
+use serde_json::json;
+
+#[cfg(feature = "blocking")]
+use crate::VaultSource;
+
+/// The Vault access an application requires.
+///
+/// # Example
+///
+/// ```
+/// use config_vault::access::AccessManifest;
+///
+/// let manifest: AccessManifest = serde_json::from_str(
+///     r#"{
+///         "requirements": [
+///             { "path": "secret/data/app/prod", "capabilities": ["read"] },
+///             { "path": "database/creds/app", "capabilities": ["read"] }
+///         ]
+///     }"#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(manifest.requirements.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct AccessManifest {
+    pub requirements: Vec<AccessRequirement>,
+}
+
+/// Capabilities required on a single Vault API path (without the `v1/` prefix).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AccessRequirement {
+    pub path: String,
+    pub capabilities: Vec<String>,
+}
+
+/// Capabilities the token lacks on a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingGrant {
+    pub path: String,
+    pub capabilities: Vec<String>,
+}
+
+impl fmt::Display for MissingGrant {
+    /// Formats the grant as the policy stanza that would fix it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let capabilities: Vec<String> = self
+            .capabilities
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect();
+        write!(
+            f,
+            "path \"{}\" {{\n  capabilities = [{}]\n}}",
+            self.path,
+            capabilities.join(", ")
+        )
+    }
+}
+
+/// Checks the token of `source` against `manifest`.
+///
+/// Capabilities are resolved with Vault's `sys/capabilities-self` endpoint on the
+/// server `source` points at. A `root` capability satisfies every requirement.
+///
+/// # Returns
+///
+/// * `Result<Vec<MissingGrant>, ConfigError>` - The missing grants (empty when the
+///   token has every required capability) or an error if Vault cannot be queried.
+///
+/// # Example
+///
+/// ```no_run
+/// use config_vault::access::{verify_access, AccessManifest, AccessRequirement};
+/// use config_vault::VaultSource;
+///
+/// let source = VaultSource::new(
+///     "http://127.0.0.1:8200",
+///     "hvs.EXAMPLE_TOKEN",
+///     "secret",
+///     "dev",
+/// );
+/// let manifest = AccessManifest {
+///     requirements: vec![AccessRequirement {
+///         path: "secret/data/dev".to_string(),
+///         capabilities: vec!["read".to_string()],
+///     }],
+/// };
+///
+/// for grant in verify_access(&source, &manifest)? {
+///     println!("{}", grant);
+/// }
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+#[cfg(feature = "blocking")]
+pub fn verify_access(
+    source: &VaultSource,
+    manifest: &AccessManifest,
+) -> Result<Vec<MissingGrant>, ConfigError> {
+    if manifest.requirements.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let paths: Vec<&str> = manifest
+        .requirements
+        .iter()
+        .map(|r| r.path.as_str())
+        .collect();
+
+    let url = source.build_api_url("v1/sys/capabilities-self")?;
+    let client = source.http_client()?;
+    let token = source.resolve_token(&client)?;
+    let granted = source.send(
+        client.post(url).json(&json!({ "paths": paths })),
+        Some(&token),
+    )?;
+
+    let mut missing = Vec::new();
+    for requirement in &manifest.requirements {
+        let has: Vec<&str> = granted
+            .get(&requirement.path)
+            .and_then(|v| v.as_array())
+            .map(|caps| caps.iter().filter_map(|c| c.as_str()).collect())
+            .unwrap_or_default();
+
+        if has.contains(&"root") {
+            continue;
+        }
+
+        let lacking: Vec<String> = requirement
+            .capabilities
+            .iter()
+            .filter(|c| !has.contains(&c.as_str()))
+            .cloned()
+            .collect();
+
+        if !lacking.is_empty() {
+            missing.push(MissingGrant {
+                path: requirement.path.clone(),
+                capabilities: lacking,
+            });
+        }
+    }
+
+    Ok(missing)
+}
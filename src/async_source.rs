@@ -0,0 +1,206 @@
+//! Asynchronous transport built on `reqwest::Client`.
+//!
+//! The blocking client panics when `collect()` runs inside a tokio runtime. This module
+//! implements `config::AsyncSource` for [`VaultSource`] so async services can use
+//! `ConfigBuilder<AsyncState>::add_async_source` instead. It is compiled only with the
+//! `async` feature.
+
+use std::sync::PoisonError;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use config::{AsyncSource, ConfigError, Map, Value};
+use reqwest::{Client, RequestBuilder, Response};
+
+use crate::proxy::ProxySetting;
+use crate::{diagnostics, retry_delay, tls, transport, AgentCacheStatus, VaultSource};
+
+impl VaultSource {
+    /// Builds the async HTTP client used to talk to Vault.
+    pub(crate) fn async_http_client(&self) -> Result<Client, ConfigError> {
+        if let Some(client) = &self.async_http_client {
+            return Ok(client.clone());
+        }
+        let mut builder = Client::builder();
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        let ca_certificates = tls::load_ca_certificates(&self.ca_certificates)?;
+        if !ca_certificates.is_empty() {
+            builder = builder.tls_built_in_root_certs(false);
+        }
+        for certificate in ca_certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(identity) = &self.client_identity {
+            builder = builder.identity(identity.load()?);
+        }
+        if self.accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        match self.proxy.proxy()? {
+            Some(proxy) => builder = builder.proxy(proxy),
+            None if matches!(self.proxy, ProxySetting::Direct) => builder = builder.no_proxy(),
+            None => {}
+        }
+        #[cfg(feature = "blocking")]
+        if let Some(identity) = self.auth.as_ref().and_then(|auth| auth.identity()) {
+            builder = builder.identity(identity);
+        }
+        builder
+            .build()
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))
+    }
+
+    /// Returns the token for the next requests, logging in with the auth method if set.
+    ///
+    /// Auth methods are blocking, so the login runs on tokio's blocking thread pool.
+    async fn resolve_token_async(&self) -> Result<String, ConfigError> {
+        #[cfg(feature = "blocking")]
+        if self.auth.is_some() {
+            if let Some(token) = self.login_cache.as_ref().and_then(|cache| cache.token()) {
+                return Ok(token);
+            }
+            let source = self.clone();
+            return tokio::task::spawn_blocking(move || {
+                source.resolve_token(&source.http_client()?)
+            })
+            .await
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+        }
+
+        Ok(self.vault_token.clone())
+    }
+
+    /// Async counterpart of the blocking `execute`, with the same consistency and throttling handling.
+    pub(crate) async fn execute_async(
+        &self,
+        request: RequestBuilder,
+        token: Option<&str>,
+    ) -> Result<Response, ConfigError> {
+        let mut attempt = 0;
+        loop {
+            let mut retry = request.try_clone().ok_or_else(|| {
+                ConfigError::Message("Vault request body cannot be retried".into())
+            })?;
+            for (name, value) in self.request_headers(token) {
+                retry = retry.header(name, value);
+            }
+
+            let response = match &self.transport {
+                Some(transport) => transport::send_async(transport.clone(), retry).await?,
+                None => match retry.send().await {
+                    Ok(response) => response,
+                    // Diagnosing resolves the host in DNS, which blocks.
+                    Err(e) => {
+                        let proxy = self.proxy.clone();
+                        return Err(tokio::task::spawn_blocking(move || {
+                            diagnostics::send_error(e, &proxy)
+                        })
+                        .await
+                        .map_err(|e| ConfigError::Foreign(Box::new(e)))?);
+                    }
+                },
+            };
+
+            if let Some(index) = response
+                .headers()
+                .get("X-Vault-Index")
+                .and_then(|v| v.to_str().ok())
+            {
+                *self
+                    .vault_index
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(index.to_string());
+            }
+
+            let Some(delay) = retry_delay(response.status(), response.headers(), attempt) else {
+                return Ok(response);
+            };
+
+            attempt += 1;
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncSource for VaultSource {
+    /// Implementation of the `collect` method from `AsyncSource`.
+    ///
+    /// Same as the blocking `Source::collect`, but the HTTP request to Vault is made
+    /// with the async `reqwest::Client`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use config::builder::AsyncState;
+    /// use config::{Config, ConfigBuilder, ConfigError};
+    /// use config_vault::VaultSource;
+    ///
+    /// async fn load_config() -> Result<Config, ConfigError> {
+    ///     let vault_source = VaultSource::new(
+    ///         "http://127.0.0.1:8200",
+    ///         "hvs.EXAMPLE_TOKEN",
+    ///         "secret",
+    ///         "dev",
+    ///     );
+    ///
+    ///     ConfigBuilder::<AsyncState>::default()
+    ///         .add_async_source(vault_source)
+    ///         .build()
+    ///         .await
+    /// }
+    /// ```
+    async fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        if let Some(delay) = self.take_startup_delay() {
+            tokio::time::sleep(delay).await;
+        }
+
+        self.fetch_secret_async()
+            .await
+            .inspect_err(|e| self.record_failure(e))
+    }
+}
+
+impl VaultSource {
+    /// Reads the secret from Vault with the async client and processes it.
+    async fn fetch_secret_async(&self) -> Result<Map<String, Value>, ConfigError> {
+        let started = Instant::now();
+        let url = self.build_read_url()?;
+
+        let client = self.async_http_client()?;
+        let token = self.resolve_token_async().await?;
+        let response = self.execute_async(client.get(url), Some(&token)).await?;
+
+        if response.status().is_success() {
+            let agent_cache = AgentCacheStatus::from_headers(response.headers());
+            let body = response
+                .bytes()
+                .await
+                .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+            #[cfg_attr(not(feature = "blocking"), allow(unused_mut))]
+            let mut raw = self.text_policy.parse_body(&body)?;
+            // Decryptors are blocking, like auth methods.
+            #[cfg(feature = "blocking")]
+            if !self.decryptors.is_empty() {
+                let source = self.clone();
+                raw = tokio::task::spawn_blocking(move || {
+                    source.decrypt_values(&mut raw, &source.http_client()?, &token)?;
+                    Ok::<_, ConfigError>(raw)
+                })
+                .await
+                .map_err(|e| ConfigError::Foreign(Box::new(e)))??;
+            }
+
+            self.process_secret(&raw, started, agent_cache)
+        } else {
+            let status = response.status();
+            let body = response.bytes().await.unwrap_or_default();
+            Err(self.read_failure(status, &body))
+        }
+    }
+}
@@ -0,0 +1,719 @@
+//! Authentication methods used to obtain a Vault token.
+//!
+//! A [`VaultSource`](crate::VaultSource) configured with an [`AuthMethod`] logs in
+//! lazily, when `collect` runs, instead of requiring a token up front. Built-in methods
+//! cover static tokens, the Vault CLI token, AppRole, userpass, GitHub, JWT/OIDC, TLS
+//! certificates, Kubernetes and Azure managed identities; other methods can be plugged
+//! in by implementing the trait. The AWS IAM and EC2 methods are available with the
+//! `aws` feature and the AliCloud method with the `alicloud` feature.
+//!
+//! This module requires the `blocking` feature.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use config::ConfigError;
+use reqwest::blocking::Client;
+use reqwest::Identity;
+use serde_json::{json, Value as JsonValue};
+
+use crate::{tls, VaultSource};
+
+#[cfg(feature = "alicloud")]
+mod alicloud;
+#[cfg(feature = "aws")]
+mod aws;
+mod azure;
+mod cli;
+#[cfg(any(feature = "aws", feature = "alicloud"))]
+mod signing;
+#[cfg(feature = "alicloud")]
+pub use alicloud::{AliCloudAuth, AliCloudCredentials};
+#[cfg(feature = "aws")]
+pub use aws::{AwsCredentials, AwsIamAuth, Ec2Auth};
+pub use azure::AzureAuth;
+pub use cli::{CliTokenAuth, TokenHelper};
+
+/// Default location of the service account token inside a Kubernetes pod.
+const KUBERNETES_JWT_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+
+/// Token and metadata returned by a successful login.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AuthInfo {
+    /// Token to send with subsequent requests.
+    pub client_token: String,
+    /// Accessor of the token, if Vault returned one.
+    pub accessor: Option<String>,
+    /// Policies attached to the token.
+    pub policies: Vec<String>,
+    /// Time to live of the token, if it expires.
+    pub lease_duration: Option<Duration>,
+    /// Whether the token can be renewed.
+    pub renewable: bool,
+}
+
+impl AuthInfo {
+    /// Wraps a token obtained without calling Vault.
+    pub fn from_token(token: impl Into<String>) -> Self {
+        Self {
+            client_token: token.into(),
+            accessor: None,
+            policies: Vec::new(),
+            lease_duration: None,
+            renewable: false,
+        }
+    }
+
+    /// Parses the `auth` block of a Vault login response.
+    pub fn from_response(response: &JsonValue) -> Result<Self, ConfigError> {
+        let auth = response
+            .get("auth")
+            .filter(|auth| !auth.is_null())
+            .ok_or_else(|| ConfigError::Message("Vault login response has no auth block".into()))?;
+
+        let client_token = auth
+            .get("client_token")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ConfigError::Message("Vault login response has no client_token".into()))?
+            .to_string();
+
+        Ok(Self {
+            client_token,
+            accessor: auth
+                .get("accessor")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            policies: auth
+                .get("policies")
+                .and_then(|v| v.as_array())
+                .map(|p| {
+                    p.iter()
+                        .filter_map(|p| p.as_str())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            lease_duration: auth
+                .get("lease_duration")
+                .and_then(|v| v.as_u64())
+                .filter(|&secs| secs > 0)
+                .map(Duration::from_secs),
+            renewable: auth
+                .get("renewable")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        })
+    }
+}
+
+impl fmt::Debug for AuthInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthInfo")
+            .field("client_token", &"<redacted>")
+            .field("accessor", &self.accessor)
+            .field("policies", &self.policies)
+            .field("lease_duration", &self.lease_duration)
+            .field("renewable", &self.renewable)
+            .finish()
+    }
+}
+
+/// Token of the last login, shared by the clones of a source that reuses logins.
+#[derive(Clone, Default)]
+pub(crate) struct LoginCache(Arc<Mutex<Option<CachedLogin>>>);
+
+struct CachedLogin {
+    token: String,
+    /// When to log in again; `None` for tokens that do not expire.
+    relogin_at: Option<Instant>,
+}
+
+impl LoginCache {
+    /// Returns the cached token unless it is due for a re-login.
+    pub(crate) fn token(&self) -> Option<String> {
+        let cached = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        cached
+            .as_ref()
+            .filter(|login| login.relogin_at.map_or(true, |at| Instant::now() < at))
+            .map(|login| login.token.clone())
+    }
+
+    /// Caches a login until two thirds of its time to live have passed, leaving a
+    /// third of the TTL to log in again before the token expires.
+    pub(crate) fn store(&self, info: &AuthInfo) {
+        let relogin_at = info
+            .lease_duration
+            .map(|ttl| Instant::now() + ttl.mul_f64(2.0 / 3.0));
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(CachedLogin {
+            token: info.client_token.clone(),
+            relogin_at,
+        });
+    }
+
+    /// Drops the cached token, e.g. after Vault rejected it.
+    pub(crate) fn clear(&self) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+impl fmt::Debug for LoginCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cached = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        f.debug_struct("LoginCache")
+            .field("token", &cached.as_ref().map(|_| "<redacted>"))
+            .field("relogin_at", &cached.as_ref().and_then(|c| c.relogin_at))
+            .finish()
+    }
+}
+
+/// A way of obtaining a Vault token.
+///
+/// # Example
+///
+/// ```
+/// use config::ConfigError;
+/// use config_vault::auth::{AuthClient, AuthInfo, AuthMethod};
+///
+/// /// Logs in with a token read from an environment variable.
+/// #[derive(Debug)]
+/// struct EnvToken;
+///
+/// impl AuthMethod for EnvToken {
+///     fn login(&self, _client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+///         let token = std::env::var("MY_VAULT_TOKEN")
+///             .map_err(|_| ConfigError::NotFound("MY_VAULT_TOKEN".to_string()))?;
+///         Ok(AuthInfo::from_token(token))
+///     }
+/// }
+/// ```
+pub trait AuthMethod: fmt::Debug + Send + Sync {
+    /// Logs in and returns the token to use for the following requests.
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError>;
+
+    /// Client certificate to present in the TLS handshake with Vault, for methods
+    /// that authenticate with one. The HTTP client used to log in carries it.
+    fn identity(&self) -> Option<Identity> {
+        None
+    }
+}
+
+/// HTTP access handed to an [`AuthMethod`] while it logs in.
+#[derive(Debug)]
+pub struct AuthClient<'a> {
+    pub(crate) source: &'a VaultSource,
+    pub(crate) client: &'a Client,
+}
+
+impl<'a> AuthClient<'a> {
+    pub(crate) fn new(source: &'a VaultSource, client: &'a Client) -> Self {
+        Self { source, client }
+    }
+
+    /// Returns the underlying HTTP client, e.g. to query a cloud metadata endpoint.
+    pub fn http(&self) -> &Client {
+        self.client
+    }
+
+    /// Posts `body` to the login endpoint `v1/auth/<path>` and parses the result.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - Login path below `auth/` (e.g. "approle/login")
+    /// * `body` - JSON body expected by the auth method
+    pub fn login(&self, path: &str, body: &JsonValue) -> Result<AuthInfo, ConfigError> {
+        let url = self.source.build_api_url(&format!("v1/auth/{}", path))?;
+        let response = self.source.send(self.client.post(url).json(body), None)?;
+        AuthInfo::from_response(&response)
+    }
+}
+
+/// Uses a fixed token; no login request is made.
+#[derive(Clone)]
+pub struct TokenAuth {
+    token: String,
+}
+
+impl TokenAuth {
+    /// Sends `token` with every request.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+        }
+    }
+}
+
+impl fmt::Debug for TokenAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenAuth")
+            .field("token", &"<redacted>")
+            .finish()
+    }
+}
+
+impl AuthMethod for TokenAuth {
+    fn login(&self, _client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        Ok(AuthInfo::from_token(self.token.clone()))
+    }
+}
+
+/// Logs in with the AppRole auth method (`auth/<mount>/login`).
+///
+/// # Example
+///
+/// ```
+/// use config_vault::auth::AppRoleAuth;
+/// use config_vault::VaultSource;
+///
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .auth(Box::new(AppRoleAuth::new(
+///         "my-role-id",
+///         Some("my-secret-id".to_string()),
+///     )))
+///     .mount("secret")
+///     .path("dev")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct AppRoleAuth {
+    mount: String,
+    role_id: String,
+    secret_id: Option<String>,
+}
+
+impl AppRoleAuth {
+    /// Creates an AppRole login on the default `approle` mount.
+    ///
+    /// `secret_id` may be `None` for roles that do not require one.
+    pub fn new(role_id: impl Into<String>, secret_id: Option<String>) -> Self {
+        Self {
+            mount: "approle".to_string(),
+            role_id: role_id.into(),
+            secret_id,
+        }
+    }
+
+    /// Uses an auth mount other than `approle`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+}
+
+impl fmt::Debug for AppRoleAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppRoleAuth")
+            .field("mount", &self.mount)
+            .field("role_id", &self.role_id)
+            .field("secret_id", &self.secret_id.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl AuthMethod for AppRoleAuth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        let mut body = json!({ "role_id": self.role_id });
+        if let Some(secret_id) = &self.secret_id {
+            body["secret_id"] = json!(secret_id);
+        }
+        client.login(&format!("{}/login", self.mount), &body)
+    }
+}
+
+/// Logs in with the userpass auth method (`auth/<mount>/login/<username>`).
+///
+/// Meant for development and CI environments where AppRole is more than needed.
+#[derive(Clone)]
+pub struct UserpassAuth {
+    mount: String,
+    username: String,
+    password: String,
+}
+
+impl UserpassAuth {
+    /// Creates a userpass login on the default `userpass` mount.
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            mount: "userpass".to_string(),
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+
+    /// Uses an auth mount other than `userpass`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+}
+
+impl fmt::Debug for UserpassAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserpassAuth")
+            .field("mount", &self.mount)
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+impl AuthMethod for UserpassAuth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        client.login(
+            &format!("{}/login/{}", self.mount, self.username),
+            &json!({ "password": self.password }),
+        )
+    }
+}
+
+/// Logs in with the GitHub auth method using a personal access token
+/// (`auth/<mount>/login`).
+///
+/// Meant for developer machines where access to Vault is granted through GitHub
+/// organization and team membership.
+#[derive(Clone)]
+pub struct GithubAuth {
+    mount: String,
+    token: String,
+}
+
+impl GithubAuth {
+    /// Creates a GitHub login on the default `github` mount.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            mount: "github".to_string(),
+            token: token.into(),
+        }
+    }
+
+    /// Uses an auth mount other than `github`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+}
+
+impl fmt::Debug for GithubAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GithubAuth")
+            .field("mount", &self.mount)
+            .field("token", &"<redacted>")
+            .finish()
+    }
+}
+
+impl AuthMethod for GithubAuth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        client.login(
+            &format!("{}/login", self.mount),
+            &json!({ "token": self.token }),
+        )
+    }
+}
+
+/// Logs in with the TLS certificates auth method (`auth/<mount>/login`).
+///
+/// The client certificate is presented in the TLS handshake of every request made
+/// by the source, including the login call, so Vault must terminate TLS itself.
+///
+/// # Example
+///
+/// ```no_run
+/// use config_vault::auth::CertAuth;
+/// use config_vault::VaultSource;
+///
+/// let auth = CertAuth::from_pem(
+///     &std::fs::read("client.crt").unwrap(),
+///     &std::fs::read("client.key").unwrap(),
+/// )?
+/// .with_name("web");
+///
+/// let source = VaultSource::builder()
+///     .addr("https://vault.example.com:8200")
+///     .auth(Box::new(auth))
+///     .mount("secret")
+///     .path("web")
+///     .build()?;
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+#[derive(Clone)]
+pub struct CertAuth {
+    mount: String,
+    name: Option<String>,
+    identity: Identity,
+}
+
+impl CertAuth {
+    /// Creates a certificate login on the default `cert` mount from a PEM
+    /// certificate (chain) and its PEM private key (PKCS#8, or with the `rustls`
+    /// backend also PKCS#1 or SEC1).
+    pub fn from_pem(cert: &[u8], key: &[u8]) -> Result<Self, ConfigError> {
+        tls::identity_from_pem(cert, key)
+            .map(Self::new)
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))
+    }
+
+    /// Creates a certificate login on the default `cert` mount from a DER-encoded
+    /// PKCS#12 archive. Requires the `native-tls` feature.
+    #[cfg(feature = "native-tls")]
+    pub fn from_pkcs12(der: &[u8], password: &str) -> Result<Self, ConfigError> {
+        Identity::from_pkcs12_der(der, password)
+            .map(Self::new)
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))
+    }
+
+    fn new(identity: Identity) -> Self {
+        Self {
+            mount: "cert".to_string(),
+            name: None,
+            identity,
+        }
+    }
+
+    /// Uses an auth mount other than `cert`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+
+    /// Logs in against the certificate role `name` instead of any matching role.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+impl fmt::Debug for CertAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertAuth")
+            .field("mount", &self.mount)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AuthMethod for CertAuth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        let mut body = json!({});
+        if let Some(name) = &self.name {
+            body["name"] = json!(name);
+        }
+        client.login(&format!("{}/login", self.mount), &body)
+    }
+
+    fn identity(&self) -> Option<Identity> {
+        Some(self.identity.clone())
+    }
+}
+
+/// Where a [`JwtAuth`] reads its token from.
+#[derive(Clone)]
+enum JwtSource {
+    Value(String),
+    File(PathBuf),
+    Env(String),
+}
+
+impl JwtSource {
+    fn read(&self) -> Result<String, ConfigError> {
+        match self {
+            JwtSource::Value(jwt) => Ok(jwt.clone()),
+            JwtSource::File(path) => std::fs::read_to_string(path)
+                .map(|jwt| jwt.trim().to_string())
+                .map_err(|e| ConfigError::Foreign(Box::new(e))),
+            JwtSource::Env(var) => {
+                std::env::var(var).map_err(|_| ConfigError::NotFound(var.clone()))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for JwtSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwtSource::Value(_) => f.write_str("Value(<redacted>)"),
+            JwtSource::File(path) => f.debug_tuple("File").field(path).finish(),
+            JwtSource::Env(var) => f.debug_tuple("Env").field(var).finish(),
+        }
+    }
+}
+
+/// Logs in with the JWT/OIDC auth method using a caller-supplied JWT
+/// (`auth/<mount>/login`).
+///
+/// Suited to CI pipelines that receive an OIDC token from their platform, such as
+/// GitHub Actions or GitLab CI, so no long-lived Vault secret has to be stored.
+/// Tokens read from a file or environment variable are read again at every login.
+///
+/// # Example
+///
+/// ```
+/// use config_vault::auth::JwtAuth;
+/// use config_vault::VaultSource;
+///
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .auth(Box::new(JwtAuth::from_env(
+///         "ci",
+///         "VAULT_ID_TOKEN",
+///     )))
+///     .mount("secret")
+///     .path("ci")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct JwtAuth {
+    mount: String,
+    role: String,
+    jwt: JwtSource,
+}
+
+impl JwtAuth {
+    /// Creates a JWT login for `role` on the default `jwt` mount with a fixed token.
+    pub fn new(role: impl Into<String>, jwt: impl Into<String>) -> Self {
+        Self::with_source(role.into(), JwtSource::Value(jwt.into()))
+    }
+
+    /// Creates a JWT login that reads the token from a file.
+    pub fn from_file(role: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self::with_source(role.into(), JwtSource::File(path.into()))
+    }
+
+    /// Creates a JWT login that reads the token from an environment variable.
+    pub fn from_env(role: impl Into<String>, var: impl Into<String>) -> Self {
+        Self::with_source(role.into(), JwtSource::Env(var.into()))
+    }
+
+    fn with_source(role: String, jwt: JwtSource) -> Self {
+        Self {
+            mount: "jwt".to_string(),
+            role,
+            jwt,
+        }
+    }
+
+    /// Uses an auth mount other than `jwt` (e.g. `oidc`).
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+}
+
+impl AuthMethod for JwtAuth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        client.login(
+            &format!("{}/login", self.mount),
+            &json!({ "role": self.role, "jwt": self.jwt.read()? }),
+        )
+    }
+}
+
+/// Logs in with the Kubernetes auth method using the pod's service account token.
+#[derive(Debug, Clone)]
+pub struct KubernetesAuth {
+    mount: String,
+    role: String,
+    jwt_path: PathBuf,
+}
+
+impl KubernetesAuth {
+    /// Creates a Kubernetes login for `role` on the default `kubernetes` mount.
+    ///
+    /// The service account token is read from its default location in the pod at
+    /// every login, so rotated tokens are picked up.
+    pub fn new(role: impl Into<String>) -> Self {
+        Self {
+            mount: "kubernetes".to_string(),
+            role: role.into(),
+            jwt_path: PathBuf::from(KUBERNETES_JWT_PATH),
+        }
+    }
+
+    /// Uses an auth mount other than `kubernetes`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+
+    /// Reads the service account token from another file.
+    pub fn with_jwt_path(mut self, jwt_path: impl Into<PathBuf>) -> Self {
+        self.jwt_path = jwt_path.into();
+        self
+    }
+}
+
+impl AuthMethod for KubernetesAuth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        let jwt = std::fs::read_to_string(&self.jwt_path)
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+
+        client.login(
+            &format!("{}/login", self.mount),
+            &json!({ "role": self.role, "jwt": jwt.trim() }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn login(token: &str, ttl: Option<Duration>) -> AuthInfo {
+        AuthInfo {
+            lease_duration: ttl,
+            ..AuthInfo::from_token(token)
+        }
+    }
+
+    fn relogin_at(cache: &LoginCache) -> Option<Instant> {
+        cache.0.lock().unwrap().as_ref().unwrap().relogin_at
+    }
+
+    #[test]
+    fn logins_are_due_after_two_thirds_of_their_ttl() {
+        let cache = LoginCache::default();
+        let ttl = Duration::from_secs(90);
+
+        let before = Instant::now();
+        cache.store(&login("hvs.first", Some(ttl)));
+        let after = Instant::now();
+
+        let at = relogin_at(&cache).unwrap();
+        assert!(at >= before + Duration::from_secs(60) && at <= after + Duration::from_secs(60));
+        assert_eq!(cache.token().as_deref(), Some("hvs.first"));
+    }
+
+    #[test]
+    fn due_logins_are_not_returned() {
+        let cache = LoginCache::default();
+        cache.store(&login("hvs.short", Some(Duration::from_millis(30))));
+
+        std::thread::sleep(Duration::from_millis(25));
+        assert_eq!(cache.token(), None);
+    }
+
+    #[test]
+    fn logins_without_ttl_are_kept() {
+        let cache = LoginCache::default();
+        cache.store(&login("hvs.forever", None));
+
+        assert_eq!(relogin_at(&cache), None);
+        assert_eq!(cache.token().as_deref(), Some("hvs.forever"));
+    }
+
+    #[test]
+    fn clones_share_the_login() {
+        let cache = LoginCache::default();
+        let clone = cache.clone();
+
+        cache.store(&login("hvs.shared", Some(Duration::from_secs(60))));
+        assert_eq!(clone.token().as_deref(), Some("hvs.shared"));
+
+        clone.clear();
+        assert_eq!(cache.token(), None);
+    }
+}
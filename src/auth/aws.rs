@@ -0,0 +1,560 @@
+//! AWS auth methods (IAM and EC2) and the SigV4 signing the IAM flow relies on.
+
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use config::ConfigError;
+use hmac::{Hmac, Mac};
+use reqwest::blocking::Client;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use sha2::{Digest, Sha256};
+
+use super::signing::{random_nonce, UtcDateTime};
+use super::{AuthClient, AuthInfo, AuthMethod};
+
+/// Body of the `sts:GetCallerIdentity` request signed for Vault.
+const GET_CALLER_IDENTITY: &str = "Action=GetCallerIdentity&Version=2011-06-15";
+
+/// Base address of the EC2 instance metadata service.
+const IMDS_ADDR: &str = "http://169.254.169.254";
+
+/// Base address of the ECS container credentials endpoint.
+const ECS_CREDENTIALS_ADDR: &str = "http://169.254.170.2";
+
+/// Timeout for requests to the local metadata endpoints.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// AWS credentials used to sign the login request.
+#[derive(Clone)]
+pub struct AwsCredentials {
+    /// Access key id.
+    pub access_key_id: String,
+    /// Secret access key.
+    pub secret_access_key: String,
+    /// Session token of temporary credentials.
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    pub fn new(
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+        session_token: Option<String>,
+    ) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token,
+        }
+    }
+
+    /// Reads `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and the optional
+    /// `AWS_SESSION_TOKEN`, as set on Lambda or by the AWS CLI.
+    pub fn from_env() -> Option<Self> {
+        let access_key_id = std::env::var("AWS_ACCESS_KEY_ID").ok()?;
+        let secret_access_key = std::env::var("AWS_SECRET_ACCESS_KEY").ok()?;
+        Some(Self::new(
+            access_key_id,
+            secret_access_key,
+            std::env::var("AWS_SESSION_TOKEN").ok(),
+        ))
+    }
+
+    /// Fetches the task role credentials from the ECS container credentials endpoint.
+    fn from_ecs(client: &Client) -> Result<Option<Self>, ConfigError> {
+        let Ok(relative_uri) = std::env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") else {
+            return Ok(None);
+        };
+
+        let response = metadata_get(
+            client
+                .get(format!("{}{}", ECS_CREDENTIALS_ADDR, relative_uri))
+                .timeout(METADATA_TIMEOUT),
+        )?;
+        Self::from_metadata(&response).map(Some)
+    }
+
+    /// Fetches the instance profile credentials from the EC2 metadata service.
+    fn from_imds(client: &Client) -> Result<Self, ConfigError> {
+        let token = imds_token(client)?;
+        let base = format!("{}/latest/meta-data/iam/security-credentials/", IMDS_ADDR);
+
+        let roles = imds_get(client, &token, &base)?;
+        let role = roles.lines().next().map(str::trim).unwrap_or_default();
+        if role.is_empty() {
+            return Err(ConfigError::Message(
+                "EC2 instance has no IAM role attached".into(),
+            ));
+        }
+
+        let response = imds_get(client, &token, &format!("{}{}", base, role))?;
+        let response =
+            serde_json::from_str(&response).map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+        Self::from_metadata(&response)
+    }
+
+    /// Parses the credentials document served by the ECS and EC2 metadata endpoints.
+    fn from_metadata(response: &JsonValue) -> Result<Self, ConfigError> {
+        let field = |name: &str| {
+            response
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    ConfigError::Message(format!("AWS credentials response has no {}", name))
+                })
+        };
+
+        Ok(Self::new(
+            field("AccessKeyId")?,
+            field("SecretAccessKey")?,
+            field("Token").ok(),
+        ))
+    }
+}
+
+impl fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+/// Logs in with the AWS auth method using the IAM flow (`auth/<mount>/login`).
+///
+/// A `sts:GetCallerIdentity` request is signed with SigV4 and handed to Vault,
+/// which forwards it to STS to establish the caller's identity. Unless credentials
+/// are set explicitly, they are looked up at every login in the environment, then
+/// at the ECS container credentials endpoint and finally at the EC2 instance
+/// metadata service, which covers Lambda, ECS and EC2.
+///
+/// This method requires the `aws` feature.
+///
+/// # Example
+///
+/// ```
+/// use config_vault::auth::AwsIamAuth;
+/// use config_vault::VaultSource;
+///
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .auth(Box::new(
+///         AwsIamAuth::new("my-role")
+///             .with_server_id("vault.example.com"),
+///     ))
+///     .mount("secret")
+///     .path("dev")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct AwsIamAuth {
+    mount: String,
+    role: String,
+    region: String,
+    sts_endpoint: String,
+    server_id: Option<String>,
+    credentials: Option<AwsCredentials>,
+}
+
+impl AwsIamAuth {
+    /// Creates an IAM login for `role` on the default `aws` mount, signed for the
+    /// global STS endpoint in `us-east-1`.
+    pub fn new(role: impl Into<String>) -> Self {
+        Self {
+            mount: "aws".to_string(),
+            role: role.into(),
+            region: "us-east-1".to_string(),
+            sts_endpoint: "https://sts.amazonaws.com/".to_string(),
+            server_id: None,
+            credentials: None,
+        }
+    }
+
+    /// Uses an auth mount other than `aws`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+
+    /// Signs the request for a regional STS endpoint (`https://sts.<region>.amazonaws.com/`).
+    ///
+    /// Vault must be configured with the same `sts_endpoint` and `sts_region`.
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        let region = region.into();
+        self.sts_endpoint = format!("https://sts.{}.amazonaws.com/", region);
+        self.region = region;
+        self
+    }
+
+    /// Sends the `X-Vault-AWS-IAM-Server-ID` header expected by Vault when the
+    /// mount has `iam_server_id_header_value` configured.
+    pub fn with_server_id(mut self, server_id: impl Into<String>) -> Self {
+        self.server_id = Some(server_id.into());
+        self
+    }
+
+    /// Signs with fixed credentials instead of looking them up at login.
+    pub fn with_credentials(mut self, credentials: AwsCredentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    fn credentials(&self, client: &Client) -> Result<AwsCredentials, ConfigError> {
+        if let Some(credentials) = &self.credentials {
+            return Ok(credentials.clone());
+        }
+        if let Some(credentials) = AwsCredentials::from_env() {
+            return Ok(credentials);
+        }
+        if let Some(credentials) = AwsCredentials::from_ecs(client)? {
+            return Ok(credentials);
+        }
+        AwsCredentials::from_imds(client)
+    }
+}
+
+impl AuthMethod for AwsIamAuth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        let credentials = self.credentials(client.http())?;
+        let url =
+            url::Url::parse(&self.sts_endpoint).map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| ConfigError::Message("STS endpoint has no host".into()))?;
+
+        let mut headers = vec![
+            (
+                "content-type".to_string(),
+                "application/x-www-form-urlencoded; charset=utf-8".to_string(),
+            ),
+            ("host".to_string(), host.to_string()),
+        ];
+        if let Some(server_id) = &self.server_id {
+            headers.push(("x-vault-aws-iam-server-id".to_string(), server_id.clone()));
+        }
+        sign_v4(
+            &credentials,
+            &self.region,
+            "sts",
+            "POST",
+            &url,
+            &mut headers,
+            GET_CALLER_IDENTITY,
+            SystemTime::now(),
+        );
+
+        let headers: JsonMap<String, JsonValue> = headers
+            .into_iter()
+            .map(|(name, value)| (name, json!([value])))
+            .collect();
+
+        client.login(
+            &format!("{}/login", self.mount),
+            &json!({
+                "role": self.role,
+                "iam_http_request_method": "POST",
+                "iam_request_url": BASE64.encode(url.as_str()),
+                "iam_request_body": BASE64.encode(GET_CALLER_IDENTITY),
+                "iam_request_headers": BASE64.encode(JsonValue::Object(headers).to_string()),
+            }),
+        )
+    }
+}
+
+/// Logs in with the AWS auth method using the EC2 flow (`auth/<mount>/login`).
+///
+/// The PKCS7 signature of the instance identity document is read from the EC2
+/// instance metadata service (IMDSv2) at every login. For older Vault setups that
+/// only enable the `ec2` auth type; prefer [`AwsIamAuth`] otherwise.
+///
+/// Vault binds the first login of an instance to a client nonce and rejects later
+/// logins with a different one. A random nonce is generated per `Ec2Auth`, which
+/// covers re-logins within one process; use [`Ec2Auth::with_nonce`] with a
+/// persisted value to log in again after a restart.
+///
+/// This method requires the `aws` feature.
+#[derive(Clone)]
+pub struct Ec2Auth {
+    mount: String,
+    role: Option<String>,
+    nonce: String,
+}
+
+impl Ec2Auth {
+    /// Creates an EC2 login on the default `aws` mount.
+    ///
+    /// `role` may be `None` to use the role named after the instance's AMI id.
+    pub fn new(role: Option<String>) -> Self {
+        Self {
+            mount: "aws".to_string(),
+            role,
+            nonce: random_nonce(),
+        }
+    }
+
+    /// Uses an auth mount other than `aws`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+
+    /// Sends a fixed client nonce instead of the one generated by [`Ec2Auth::new`].
+    pub fn with_nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = nonce.into();
+        self
+    }
+}
+
+impl fmt::Debug for Ec2Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ec2Auth")
+            .field("mount", &self.mount)
+            .field("role", &self.role)
+            .field("nonce", &"<redacted>")
+            .finish()
+    }
+}
+
+impl AuthMethod for Ec2Auth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        let token = imds_token(client.http())?;
+        let pkcs7 = imds_get(
+            client.http(),
+            &token,
+            &format!("{}/latest/dynamic/instance-identity/pkcs7", IMDS_ADDR),
+        )?;
+
+        let mut body = json!({
+            "pkcs7": pkcs7.replace('\n', ""),
+            "nonce": self.nonce,
+        });
+        if let Some(role) = &self.role {
+            body["role"] = json!(role);
+        }
+        client.login(&format!("{}/login", self.mount), &body)
+    }
+}
+
+/// Signs a request with AWS Signature Version 4.
+///
+/// `headers` holds lowercase header names and must include `host`; the
+/// `x-amz-date`, `x-amz-security-token` and `authorization` headers are added to it.
+#[allow(clippy::too_many_arguments)]
+fn sign_v4(
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    method: &str,
+    url: &url::Url,
+    headers: &mut Vec<(String, String)>,
+    body: &str,
+    now: SystemTime,
+) {
+    let (date, amz_date) = amz_timestamps(now);
+    headers.push(("x-amz-date".to_string(), amz_date.clone()));
+    if let Some(session_token) = &credentials.session_token {
+        headers.push(("x-amz-security-token".to_string(), session_token.clone()));
+    }
+    headers.sort();
+    let (canonical_request, signed_headers) = canonical_request(method, url, headers, body);
+
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = [date.as_str(), region, service, "aws4_request"]
+        .iter()
+        .fold(
+            format!("AWS4{}", credentials.secret_access_key).into_bytes(),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+    let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    headers.push((
+        "authorization".to_string(),
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+}
+
+/// Builds the SigV4 canonical request and its signed header list from `headers`,
+/// which must be lowercase and sorted.
+fn canonical_request(
+    method: &str,
+    url: &url::Url,
+    headers: &[(String, String)],
+    body: &str,
+) -> (String, String) {
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        url.path(),
+        url.query().unwrap_or_default(),
+        canonical_headers,
+        signed_headers,
+        hex(&Sha256::digest(body.as_bytes()))
+    );
+    (canonical_request, signed_headers)
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Formats `now` as the `YYYYMMDD` date and `YYYYMMDDTHHMMSSZ` timestamp used by SigV4.
+fn amz_timestamps(now: SystemTime) -> (String, String) {
+    let now = UtcDateTime::from_system_time(now);
+    let date = format!("{:04}{:02}{:02}", now.year, now.month, now.day);
+    let timestamp = format!(
+        "{}T{:02}{:02}{:02}Z",
+        date, now.hour, now.minute, now.second
+    );
+    (date, timestamp)
+}
+
+/// Requests an IMDSv2 session token.
+fn imds_token(client: &Client) -> Result<String, ConfigError> {
+    client
+        .put(format!("{}/latest/api/token", IMDS_ADDR))
+        .header("X-aws-ec2-metadata-token-ttl-seconds", "300")
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.text())
+        .map_err(|e| ConfigError::Foreign(Box::new(e)))
+}
+
+/// Reads an IMDSv2 path as text.
+fn imds_get(client: &Client, token: &str, url: &str) -> Result<String, ConfigError> {
+    client
+        .get(url)
+        .header("X-aws-ec2-metadata-token", token)
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.text())
+        .map_err(|e| ConfigError::Foreign(Box::new(e)))
+}
+
+fn metadata_get(request: reqwest::blocking::RequestBuilder) -> Result<JsonValue, ConfigError> {
+    request
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.json())
+        .map_err(|e| ConfigError::Foreign(Box::new(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    /// The `get-vanilla` case of the AWS SigV4 test suite.
+    fn get_vanilla() -> (AwsCredentials, url::Url, SystemTime) {
+        let credentials = AwsCredentials::new(
+            "AKIDEXAMPLE",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            None,
+        );
+        let url = url::Url::parse("https://example.amazonaws.com/").unwrap();
+        // 2015-08-30T12:36:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(1_440_938_160);
+        (credentials, url, now)
+    }
+
+    #[test]
+    fn canonical_request_matches_get_vanilla() {
+        let (_, url, _) = get_vanilla();
+        let headers = vec![
+            ("host".to_string(), "example.amazonaws.com".to_string()),
+            ("x-amz-date".to_string(), "20150830T123600Z".to_string()),
+        ];
+        let (canonical, signed_headers) = canonical_request("GET", &url, &headers, "");
+        assert_eq!(
+            canonical,
+            "GET\n/\n\nhost:example.amazonaws.com\nx-amz-date:20150830T123600Z\n\n\
+             host;x-amz-date\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(signed_headers, "host;x-amz-date");
+    }
+
+    #[test]
+    fn sign_v4_matches_get_vanilla() {
+        let (credentials, url, now) = get_vanilla();
+        let mut headers = vec![("host".to_string(), "example.amazonaws.com".to_string())];
+        sign_v4(
+            &credentials,
+            "us-east-1",
+            "service",
+            "GET",
+            &url,
+            &mut headers,
+            "",
+            now,
+        );
+
+        assert_eq!(
+            headers.last().unwrap(),
+            &(
+                "authorization".to_string(),
+                "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+                 SignedHeaders=host;x-amz-date, \
+                 Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn sign_v4_signs_the_session_token() {
+        let (mut credentials, url, now) = get_vanilla();
+        credentials.session_token = Some("token".to_string());
+        let mut headers = vec![("host".to_string(), "example.amazonaws.com".to_string())];
+        sign_v4(
+            &credentials,
+            "us-east-1",
+            "service",
+            "GET",
+            &url,
+            &mut headers,
+            "",
+            now,
+        );
+
+        let (_, authorization) = headers.last().unwrap();
+        assert!(authorization.contains("SignedHeaders=host;x-amz-date;x-amz-security-token,"));
+        assert!(headers.contains(&("x-amz-security-token".to_string(), "token".to_string())));
+    }
+}
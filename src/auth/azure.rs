@@ -0,0 +1,148 @@
+//! Azure auth method using a managed identity token.
+
+use std::time::Duration;
+
+use config::ConfigError;
+use serde_json::json;
+
+use super::{AuthClient, AuthInfo, AuthMethod};
+
+/// Managed identity token endpoint of the Azure instance metadata service.
+const AZURE_IMDS_TOKEN_URL: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+
+/// Timeout for requests to the instance metadata service.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Logs in with the Azure auth method using a managed identity (`auth/<mount>/login`).
+///
+/// A token for the managed identity is requested from the Azure instance metadata
+/// service at every login and sent to Vault together with the location of the VM
+/// or scale set, which Vault checks against the role's bound parameters.
+///
+/// # Example
+///
+/// ```
+/// use config_vault::auth::AzureAuth;
+/// use config_vault::VaultSource;
+///
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .auth(Box::new(
+///         AzureAuth::new(
+///             "my-role",
+///             "00000000-0000-0000-0000-000000000000",
+///             "my-resource-group",
+///         )
+///         .with_vmss_name("my-scale-set"),
+///     ))
+///     .mount("secret")
+///     .path("dev")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct AzureAuth {
+    mount: String,
+    role: String,
+    resource: String,
+    client_id: Option<String>,
+    subscription_id: String,
+    resource_group_name: String,
+    vm_name: Option<String>,
+    vmss_name: Option<String>,
+}
+
+impl AzureAuth {
+    /// Creates an Azure login for `role` on the default `azure` mount, requesting a
+    /// token for `https://management.azure.com/` from the system-assigned identity.
+    pub fn new(
+        role: impl Into<String>,
+        subscription_id: impl Into<String>,
+        resource_group_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            mount: "azure".to_string(),
+            role: role.into(),
+            resource: "https://management.azure.com/".to_string(),
+            client_id: None,
+            subscription_id: subscription_id.into(),
+            resource_group_name: resource_group_name.into(),
+            vm_name: None,
+            vmss_name: None,
+        }
+    }
+
+    /// Uses an auth mount other than `azure`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+
+    /// Requests the token for another resource; it must match the `resource`
+    /// configured on the Vault mount.
+    pub fn with_resource(mut self, resource: impl Into<String>) -> Self {
+        self.resource = resource.into();
+        self
+    }
+
+    /// Uses the user-assigned identity with this client id.
+    pub fn with_client_id(mut self, client_id: impl Into<String>) -> Self {
+        self.client_id = Some(client_id.into());
+        self
+    }
+
+    /// Name of the virtual machine, for roles bound to VMs.
+    pub fn with_vm_name(mut self, vm_name: impl Into<String>) -> Self {
+        self.vm_name = Some(vm_name.into());
+        self
+    }
+
+    /// Name of the virtual machine scale set, e.g. the node pool of an AKS cluster.
+    pub fn with_vmss_name(mut self, vmss_name: impl Into<String>) -> Self {
+        self.vmss_name = Some(vmss_name.into());
+        self
+    }
+}
+
+impl AuthMethod for AzureAuth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        let mut query = vec![
+            ("api-version", "2018-02-01"),
+            ("resource", self.resource.as_str()),
+        ];
+        if let Some(client_id) = &self.client_id {
+            query.push(("client_id", client_id));
+        }
+
+        let response: serde_json::Value = client
+            .http()
+            .get(AZURE_IMDS_TOKEN_URL)
+            .query(&query)
+            .header("Metadata", "true")
+            .timeout(METADATA_TIMEOUT)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+        let jwt = response
+            .get("access_token")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                ConfigError::Message("Azure metadata response has no access_token".into())
+            })?;
+
+        let mut body = json!({
+            "role": self.role,
+            "jwt": jwt,
+            "subscription_id": self.subscription_id,
+            "resource_group_name": self.resource_group_name,
+        });
+        if let Some(vm_name) = &self.vm_name {
+            body["vm_name"] = json!(vm_name);
+        }
+        if let Some(vmss_name) = &self.vmss_name {
+            body["vmss_name"] = json!(vmss_name);
+        }
+        client.login(&format!("{}/login", self.mount), &body)
+    }
+}
@@ -0,0 +1,347 @@
+//! Token resolution compatible with the Vault CLI.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use config::ConfigError;
+
+use super::{AuthClient, AuthInfo, AuthMethod};
+
+/// Resolves the token the way the Vault CLI does.
+///
+/// The `VAULT_TOKEN` environment variable is used first. Otherwise the token is
+/// read from the token helper: the external command configured as `token_helper`
+/// in the CLI configuration file (`VAULT_CONFIG_PATH`, by default `~/.vault`) or
+/// set with [`CliTokenAuth::with_token_helper`], and without one the
+/// `~/.vault-token` file written by `vault login`.
+///
+/// No login request is made. The token is resolved again at every `collect`, so a
+/// fresh `vault login` is picked up without restarting. Meant for local development,
+/// where tokens should not be pasted into code.
+///
+/// # Example
+///
+/// ```
+/// use config_vault::auth::CliTokenAuth;
+/// use config_vault::VaultSource;
+///
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .auth(Box::new(CliTokenAuth::new()))
+///     .mount("secret")
+///     .path("dev")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CliTokenAuth {
+    token_helper: Option<TokenHelper>,
+}
+
+impl CliTokenAuth {
+    /// Resolves the token like the CLI, with the token helper of its configuration
+    /// file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses this token helper instead of the one in the CLI configuration file.
+    pub fn with_token_helper(mut self, helper: TokenHelper) -> Self {
+        self.token_helper = Some(helper);
+        self
+    }
+
+    /// Returns the token the Vault CLI would use, if any.
+    pub fn resolve(&self) -> Result<Option<String>, ConfigError> {
+        if let Some(token) = std::env::var("VAULT_TOKEN").ok().filter(|t| !t.is_empty()) {
+            return Ok(Some(token));
+        }
+
+        if let Some(helper) = &self.token_helper {
+            return helper.get();
+        }
+        if let Some(helper) = TokenHelper::from_cli_config()? {
+            return helper.get();
+        }
+
+        let Some(path) = home_dir().map(|home| home.join(".vault-token")) else {
+            return Ok(None);
+        };
+        match std::fs::read_to_string(path) {
+            Ok(token) => Ok(Some(token.trim().to_string()).filter(|t| !t.is_empty())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ConfigError::Foreign(Box::new(e))),
+        }
+    }
+}
+
+impl AuthMethod for CliTokenAuth {
+    fn login(&self, _client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        self.resolve()?.map(AuthInfo::from_token).ok_or_else(|| {
+            ConfigError::Message(
+                "No Vault token found in VAULT_TOKEN, the token helper or ~/.vault-token; \
+                 run `vault login`"
+                    .into(),
+            )
+        })
+    }
+}
+
+/// An external token helper, following the Vault CLI `token_helper` convention.
+///
+/// The helper is a program called with a single argument: `get` prints the stored
+/// token on stdout, `store` reads a token from stdin and `erase` removes it. Helpers
+/// typically keep the token in the operating system's keychain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenHelper {
+    command: PathBuf,
+}
+
+impl TokenHelper {
+    /// Uses the helper program at `command`, e.g. `/usr/local/bin/vault-token-helper`.
+    pub fn new(command: impl Into<PathBuf>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// Reads the `token_helper` setting of the Vault CLI configuration file, if set.
+    ///
+    /// Only this setting is read from the file.
+    pub fn from_cli_config() -> Result<Option<Self>, ConfigError> {
+        let path = match std::env::var_os("VAULT_CONFIG_PATH") {
+            Some(path) => PathBuf::from(path),
+            None => match home_dir() {
+                Some(home) => home.join(".vault"),
+                None => return Ok(None),
+            },
+        };
+
+        let config = match std::fs::read_to_string(&path) {
+            Ok(config) => config,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(ConfigError::Foreign(Box::new(e))),
+        };
+
+        Ok(config.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "token_helper")
+                .then(|| value.trim().trim_matches('"'))
+                .filter(|command| !command.is_empty())
+                .map(|command| Self::new(PathBuf::from(command)))
+        }))
+    }
+
+    /// Returns the stored token, or `None` if the helper has none.
+    pub fn get(&self) -> Result<Option<String>, ConfigError> {
+        let output = self.run("get", None)?;
+        let token = String::from_utf8(output)
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))?
+            .trim()
+            .to_string();
+        Ok(Some(token).filter(|t| !t.is_empty()))
+    }
+
+    /// Stores `token` with the helper.
+    pub fn store(&self, token: &str) -> Result<(), ConfigError> {
+        self.run("store", Some(token)).map(|_| ())
+    }
+
+    /// Removes the stored token.
+    pub fn erase(&self) -> Result<(), ConfigError> {
+        self.run("erase", None).map(|_| ())
+    }
+
+    fn run(&self, operation: &str, stdin: Option<&str>) -> Result<Vec<u8>, ConfigError> {
+        let error = |e: std::io::Error| {
+            ConfigError::Message(format!(
+                "Token helper {} failed to {}: {}",
+                self.command.display(),
+                operation,
+                e
+            ))
+        };
+
+        let mut child = Command::new(&self.command)
+            .arg(operation)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(error)?;
+        if let Some(input) = stdin {
+            if let Some(mut pipe) = child.stdin.take() {
+                pipe.write_all(input.as_bytes()).map_err(error)?;
+            }
+        }
+        let output = child.wait_with_output().map_err(error)?;
+
+        if !output.status.success() {
+            return Err(ConfigError::Message(format!(
+                "Token helper {} failed to {} ({}): {}",
+                self.command.display(),
+                operation,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// Home directory of the current user, as used by the Vault CLI.
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::env::tests::with_env;
+
+    /// A fresh directory for the files of one test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("config-vault-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writes an executable shell script.
+    #[cfg(unix)]
+    fn script(path: &Path, body: &str) -> TokenHelper {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::write(path, format!("#!/bin/sh\n{}", body)).unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        TokenHelper::new(path)
+    }
+
+    /// A helper that keeps the token in a file next to it.
+    #[cfg(unix)]
+    fn file_helper(dir: &Path) -> TokenHelper {
+        script(
+            &dir.join("helper"),
+            r#"store="$(dirname "$0")/token"
+case "$1" in
+    get) if [ -f "$store" ]; then cat "$store"; fi ;;
+    store) cat > "$store" ;;
+    erase) rm -f "$store" ;;
+    *) echo "unknown operation $1" >&2; exit 2 ;;
+esac
+"#,
+        )
+    }
+
+    fn config_helper(config: &str) -> Option<TokenHelper> {
+        let dir = temp_dir("cli-config");
+        let path = dir.join("vault.hcl");
+        std::fs::write(&path, config).unwrap();
+        let helper = with_env(&[("VAULT_CONFIG_PATH", path.to_str().unwrap())], || {
+            TokenHelper::from_cli_config()
+        });
+        std::fs::remove_dir_all(dir).unwrap();
+        helper.unwrap()
+    }
+
+    #[test]
+    fn from_cli_config_reads_the_token_helper() {
+        assert_eq!(
+            config_helper("token_helper = \"/usr/local/bin/helper\"\n"),
+            Some(TokenHelper::new("/usr/local/bin/helper"))
+        );
+        assert_eq!(
+            config_helper("# CLI settings\nui = true\n  token_helper=/opt/helper  \n"),
+            Some(TokenHelper::new("/opt/helper"))
+        );
+    }
+
+    #[test]
+    fn from_cli_config_without_a_token_helper() {
+        assert_eq!(config_helper("ui = true\n"), None);
+        assert_eq!(config_helper("token_helper = \"\"\n"), None);
+        assert_eq!(config_helper("token_helper_path = \"/opt/helper\"\n"), None);
+
+        let missing = temp_dir("cli-config-missing").join("vault.hcl");
+        let helper = with_env(&[("VAULT_CONFIG_PATH", missing.to_str().unwrap())], || {
+            TokenHelper::from_cli_config()
+        });
+        assert_eq!(helper.unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn helpers_get_store_and_erase_tokens() {
+        let dir = temp_dir("token-helper");
+        let helper = file_helper(&dir);
+
+        assert_eq!(helper.get().unwrap(), None);
+        helper.store("hvs.STORED").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("token")).unwrap(),
+            "hvs.STORED"
+        );
+        assert_eq!(helper.get().unwrap().as_deref(), Some("hvs.STORED"));
+        helper.erase().unwrap();
+        assert_eq!(helper.get().unwrap(), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn helper_failures_are_errors() {
+        let dir = temp_dir("token-helper-failure");
+        let helper = script(
+            &dir.join("helper"),
+            "echo \"keychain is locked\" >&2\nexit 1\n",
+        );
+
+        let error = helper.get().unwrap_err().to_string();
+        assert!(
+            error.starts_with(&format!(
+                "Token helper {} failed to get (",
+                dir.join("helper").display()
+            )),
+            "{}",
+            error
+        );
+        assert!(error.ends_with("): keychain is locked"), "{}", error);
+
+        let missing = TokenHelper::new(dir.join("missing"));
+        let error = missing.erase().unwrap_err().to_string();
+        assert!(
+            error.starts_with(&format!(
+                "Token helper {} failed to erase: ",
+                dir.join("missing").display()
+            )),
+            "{}",
+            error
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn vault_token_takes_precedence_over_the_helper() {
+        let dir = temp_dir("token-helper-precedence");
+        let helper = file_helper(&dir);
+        helper.store("hvs.STORED").unwrap();
+        let auth = CliTokenAuth::new().with_token_helper(helper);
+
+        let from_env = with_env(&[("VAULT_TOKEN", "hvs.FROM_ENV")], || auth.resolve());
+        assert_eq!(from_env.unwrap().as_deref(), Some("hvs.FROM_ENV"));
+        let from_helper = with_env(&[], || auth.resolve());
+        assert_eq!(from_helper.unwrap().as_deref(), Some("hvs.STORED"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+//! Blocking transport built on `reqwest::blocking`.
+//!
+//! This module provides the `config::Source` implementation of [`VaultSource`] and is
+//! compiled only with the `blocking` feature (enabled by default).
+
+use std::sync::PoisonError;
+use std::time::Instant;
+
+use config::{ConfigError, Map, Source, Value};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde_json::Value as JsonValue;
+
+use crate::auth::{AuthClient, LoginCache};
+use crate::events::EventKind;
+use crate::proxy::ProxySetting;
+use crate::{diagnostics, tls, transport};
+use crate::{retry_delay, AgentCacheStatus, VaultSource};
+
+impl VaultSource {
+    /// Builds the blocking HTTP client used to talk to Vault.
+    pub(crate) fn http_client(&self) -> Result<Client, ConfigError> {
+        if let Some(client) = &self.http_client {
+            return Ok(client.clone());
+        }
+        let mut builder = Client::builder();
+        // `None` also lifts the 30 second default of the blocking client.
+        builder = builder.timeout(self.timeout);
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        let ca_certificates = tls::load_ca_certificates(&self.ca_certificates)?;
+        if !ca_certificates.is_empty() {
+            builder = builder.tls_built_in_root_certs(false);
+        }
+        for certificate in ca_certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(identity) = &self.client_identity {
+            builder = builder.identity(identity.load()?);
+        }
+        if self.accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        match self.proxy.proxy()? {
+            Some(proxy) => builder = builder.proxy(proxy),
+            None if matches!(self.proxy, ProxySetting::Direct) => builder = builder.no_proxy(),
+            None => {}
+        }
+        if let Some(identity) = self.auth.as_ref().and_then(|auth| auth.identity()) {
+            builder = builder.identity(identity);
+        }
+        builder
+            .build()
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))
+    }
+
+    /// Returns the token for the next requests, logging in with the auth method if set.
+    pub(crate) fn resolve_token(&self, client: &Client) -> Result<String, ConfigError> {
+        match &self.auth {
+            Some(auth) => {
+                if let Some(token) = self.login_cache.as_ref().and_then(LoginCache::token) {
+                    return Ok(token);
+                }
+                let info = auth.login(&AuthClient::new(self, client))?;
+                self.record_event(EventKind::Login);
+                if let Some(cache) = &self.login_cache {
+                    cache.store(&info);
+                }
+                Ok(info.client_token)
+            }
+            None => Ok(self.vault_token.clone()),
+        }
+    }
+
+    /// Sends a request and parses the JSON response body, if any.
+    pub(crate) fn send(
+        &self,
+        request: RequestBuilder,
+        token: Option<&str>,
+    ) -> Result<JsonValue, ConfigError> {
+        let response = self.execute(request, token)?;
+
+        if !response.status().is_success() {
+            return Err(ConfigError::Message(format!(
+                "Vault request failed: {}",
+                response.status()
+            )));
+        }
+
+        let body = response
+            .text()
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+
+        if body.is_empty() {
+            Ok(JsonValue::Null)
+        } else {
+            serde_json::from_str(&body).map_err(|e| ConfigError::Foreign(Box::new(e)))
+        }
+    }
+
+    /// Executes a request with Vault Enterprise consistency and throttling handling.
+    ///
+    /// `token` is sent as `X-Vault-Token`; login requests pass `None`. The namespace, if
+    /// any, is sent with every request, logins included.
+    ///
+    /// The last `X-Vault-Index` returned by Vault is sent along with every request, so
+    /// a performance standby only answers once it has caught up with earlier writes.
+    /// Standbys that have not caught up yet answer 412, which is retried with a short
+    /// exponential backoff. Throttled requests (429/503) are retried after the delay
+    /// Vault asks for in `Retry-After`.
+    pub(crate) fn execute(
+        &self,
+        request: RequestBuilder,
+        token: Option<&str>,
+    ) -> Result<Response, ConfigError> {
+        let mut attempt = 0;
+        loop {
+            let mut retry = request.try_clone().ok_or_else(|| {
+                ConfigError::Message("Vault request body cannot be retried".into())
+            })?;
+            for (name, value) in self.request_headers(token) {
+                retry = retry.header(name, value);
+            }
+
+            let response = match &self.transport {
+                Some(transport) => transport::send_blocking(transport.as_ref(), retry)?,
+                None => retry
+                    .send()
+                    .map_err(|e| diagnostics::send_error(e, &self.proxy))?,
+            };
+
+            if let Some(index) = response
+                .headers()
+                .get("X-Vault-Index")
+                .and_then(|v| v.to_str().ok())
+            {
+                *self
+                    .vault_index
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(index.to_string());
+            }
+
+            let Some(delay) = retry_delay(response.status(), response.headers(), attempt) else {
+                return Ok(response);
+            };
+
+            attempt += 1;
+            std::thread::sleep(delay);
+        }
+    }
+}
+
+impl Source for VaultSource {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    /// Implementation of the `collect` method from `Source`.
+    ///
+    /// This method makes an HTTP request to the Vault API to obtain
+    /// configuration values stored in the specified secret.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Map<String, Value>, ConfigError>` - A map with configuration values
+    ///   or an error if the request fails or the response format is not as expected.
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        if let Some(delay) = self.take_startup_delay() {
+            std::thread::sleep(delay);
+        }
+
+        self.fetch_secret().inspect_err(|e| self.record_failure(e))
+    }
+}
+
+impl VaultSource {
+    /// Reads the secret from Vault and processes it.
+    fn fetch_secret(&self) -> Result<Map<String, Value>, ConfigError> {
+        let started = Instant::now();
+        let url = self.build_read_url()?;
+
+        let client = self.http_client()?;
+        let token = self.resolve_token(&client)?;
+        let response = self.execute(client.get(url), Some(&token))?;
+
+        if response.status().is_success() {
+            let agent_cache = AgentCacheStatus::from_headers(response.headers());
+            let body = response
+                .bytes()
+                .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+            let mut raw = self.text_policy.parse_body(&body)?;
+            self.decrypt_values(&mut raw, &client, &token)?;
+
+            self.process_secret(&raw, started, agent_cache)
+        } else {
+            let status = response.status();
+            let body = response.bytes().unwrap_or_default();
+            Err(self.read_failure(status, &body))
+        }
+    }
+}
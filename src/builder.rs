@@ -0,0 +1,432 @@
+//! Step-by-step construction of a [`VaultSource`].
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use config::{ConfigError, Map, Value};
+use serde_json::Value as JsonValue;
+use url::Url;
+
+#[cfg(feature = "blocking")]
+use crate::auth::AuthMethod;
+#[cfg(feature = "blocking")]
+use crate::decrypt::Decryptor;
+use crate::events::EventLog;
+use crate::transport::VaultTransport;
+#[allow(deprecated)]
+use crate::KvVersion;
+use crate::{diagnostics, tls};
+use crate::{EngineKind, KeyCase, LoadReport, TextPolicy, VaultSource};
+
+/// Builder for [`VaultSource`], created with [`VaultSource::builder`].
+///
+/// The Vault address, mount, path and either a token or an auth method are required. [`build`](Self::build)
+/// validates the address and TLS certificates up front, so a malformed URL or an
+/// unreadable certificate is reported when the source is created rather than on the
+/// first `collect`.
+#[derive(Debug, Clone)]
+pub struct VaultSourceBuilder {
+    source: VaultSource,
+}
+
+impl VaultSourceBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            source: VaultSource::with_engine(
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                EngineKind::default(),
+            ),
+        }
+    }
+
+    /// Sets the complete URL of the Vault server (e.g. "http://127.0.0.1:8200").
+    pub fn addr(mut self, addr: impl Into<String>) -> Self {
+        self.source.vault_addr = addr.into();
+        self
+    }
+
+    /// Sets the authentication token for Vault.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.source.vault_token = token.into();
+        self
+    }
+
+    /// See [`VaultSource::set_auth`]. Replaces the need for a static token.
+    #[cfg(feature = "blocking")]
+    pub fn auth(mut self, auth: Box<dyn AuthMethod>) -> Self {
+        self.source.set_auth(auth);
+        self
+    }
+
+    /// See [`VaultSource::set_reuse_login`].
+    #[cfg(feature = "blocking")]
+    pub fn reuse_login(mut self, enabled: bool) -> Self {
+        self.source.set_reuse_login(enabled);
+        self
+    }
+
+    /// See [`VaultSource::add_decryptor`].
+    #[cfg(feature = "blocking")]
+    pub fn decryptor(mut self, prefix: impl Into<String>, decryptor: Box<dyn Decryptor>) -> Self {
+        self.source.add_decryptor(prefix, decryptor);
+        self
+    }
+
+    /// See [`VaultSource::set_namespace`].
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.source.set_namespace(Some(namespace.into()));
+        self
+    }
+
+    /// See [`VaultSource::add_ca_certificate`].
+    pub fn ca_certificate(mut self, pem: Vec<u8>) -> Self {
+        self.source.add_ca_certificate(pem);
+        self
+    }
+
+    /// See [`VaultSource::add_ca_certificate_file`].
+    pub fn ca_certificate_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.source.add_ca_certificate_file(path);
+        self
+    }
+
+    /// See [`VaultSource::add_ca_directory`].
+    pub fn ca_directory(mut self, path: impl Into<PathBuf>) -> Self {
+        self.source.add_ca_directory(path);
+        self
+    }
+
+    /// See [`VaultSource::set_client_certificate`].
+    pub fn client_certificate(mut self, cert: Vec<u8>, key: Vec<u8>) -> Self {
+        self.source.set_client_certificate(cert, key);
+        self
+    }
+
+    /// See [`VaultSource::set_client_certificate_files`].
+    pub fn client_certificate_files(
+        mut self,
+        cert: impl Into<PathBuf>,
+        key: impl Into<PathBuf>,
+    ) -> Self {
+        self.source.set_client_certificate_files(cert, key);
+        self
+    }
+
+    /// See [`VaultSource::set_client_pkcs12`].
+    #[cfg(feature = "native-tls")]
+    pub fn client_pkcs12(mut self, der: Vec<u8>, password: impl Into<String>) -> Self {
+        self.source.set_client_pkcs12(der, password);
+        self
+    }
+
+    /// See [`VaultSource::set_danger_accept_invalid_certs`].
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.source.set_danger_accept_invalid_certs(accept);
+        self
+    }
+
+    /// See [`VaultSource::set_proxy`].
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.source.set_proxy(url);
+        self
+    }
+
+    /// See [`VaultSource::set_proxy_basic_auth`]. Must be called after
+    /// [`proxy`](Self::proxy).
+    pub fn proxy_basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.source.set_proxy_basic_auth(username, password);
+        self
+    }
+
+    /// See [`VaultSource::set_no_proxy`].
+    pub fn no_proxy(mut self) -> Self {
+        self.source.set_no_proxy();
+        self
+    }
+
+    /// See [`VaultSource::set_http_client`].
+    #[cfg(feature = "blocking")]
+    pub fn http_client(mut self, client: reqwest::blocking::Client) -> Self {
+        self.source.set_http_client(client);
+        self
+    }
+
+    /// See [`VaultSource::set_async_http_client`].
+    #[cfg(feature = "async")]
+    pub fn async_http_client(mut self, client: reqwest::Client) -> Self {
+        self.source.set_async_http_client(client);
+        self
+    }
+
+    /// See [`VaultSource::set_transport`].
+    pub fn transport(mut self, transport: Box<dyn VaultTransport>) -> Self {
+        self.source.set_transport(transport);
+        self
+    }
+
+    /// Sets the name of the KV engine mount (e.g. "secret").
+    pub fn mount(mut self, mount: impl Into<String>) -> Self {
+        self.source.vault_mount = mount.into();
+        self
+    }
+
+    /// Sets the path to the secret within the mount (e.g. "dev").
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.source.vault_path = path.into();
+        self
+    }
+
+    /// Sets the KV engine version. Defaults to [`KvVersion::V2`].
+    #[deprecated(note = "use engine with EngineKind::Kv1 or EngineKind::Kv2")]
+    #[allow(deprecated)]
+    pub fn kv_version(mut self, kv_version: KvVersion) -> Self {
+        self.source.set_kv_version(kv_version);
+        self
+    }
+
+    /// See [`VaultSource::set_engine`].
+    pub fn engine(mut self, engine: EngineKind) -> Self {
+        self.source.set_engine(engine);
+        self
+    }
+
+    /// See [`VaultSource::set_timeout`]. Defaults to
+    /// [`DEFAULT_TIMEOUT`](crate::DEFAULT_TIMEOUT).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.source.set_timeout(Some(timeout));
+        self
+    }
+
+    /// Disables the request timeout; see [`VaultSource::set_timeout`].
+    pub fn no_timeout(mut self) -> Self {
+        self.source.set_timeout(None);
+        self
+    }
+
+    /// See [`VaultSource::set_connect_timeout`].
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.source.set_connect_timeout(Some(timeout));
+        self
+    }
+
+    /// See [`VaultSource::add_pem_bundle`].
+    pub fn pem_bundle(
+        mut self,
+        key: impl Into<String>,
+        fields: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.source.add_pem_bundle(key, fields);
+        self
+    }
+
+    /// See [`VaultSource::set_interpolation`].
+    pub fn interpolation(mut self, enabled: bool) -> Self {
+        self.source.set_interpolation(enabled);
+        self
+    }
+
+    /// See [`VaultSource::set_flatten_separator`].
+    pub fn flatten_separator(mut self, separator: impl Into<String>) -> Self {
+        self.source.set_flatten_separator(Some(separator.into()));
+        self
+    }
+
+    /// See [`VaultSource::set_json_key`].
+    pub fn json_key(mut self, key: impl Into<String>) -> Self {
+        self.source.set_json_key(Some(key.into()));
+        self
+    }
+
+    /// See [`VaultSource::set_nested_at`].
+    pub fn nested_at(mut self, parent: impl Into<String>) -> Self {
+        self.source.set_nested_at(Some(parent.into()));
+        self
+    }
+
+    /// See [`VaultSource::set_expiry_keys`].
+    pub fn expiry_keys(mut self, enabled: bool) -> Self {
+        self.source.set_expiry_keys(enabled);
+        self
+    }
+
+    /// See [`VaultSource::add_computed_key`].
+    pub fn computed_key<F>(mut self, key: impl Into<String>, compute: F) -> Self
+    where
+        F: Fn(&Map<String, Value>) -> Result<Value, ConfigError> + Send + Sync + 'static,
+    {
+        self.source.add_computed_key(key, compute);
+        self
+    }
+
+    /// See [`VaultSource::add_required_key`].
+    pub fn required_key(mut self, key: impl Into<String>) -> Self {
+        self.source.add_required_key(key);
+        self
+    }
+
+    /// See [`VaultSource::add_include_key`].
+    pub fn include_key(mut self, pattern: impl Into<String>) -> Self {
+        self.source.add_include_key(pattern);
+        self
+    }
+
+    /// See [`VaultSource::add_exclude_key`].
+    pub fn exclude_key(mut self, pattern: impl Into<String>) -> Self {
+        self.source.add_exclude_key(pattern);
+        self
+    }
+
+    /// See [`VaultSource::set_max_keys`].
+    pub fn max_keys(mut self, max_keys: usize) -> Self {
+        self.source.set_max_keys(max_keys);
+        self
+    }
+
+    /// See [`VaultSource::set_max_value_size`].
+    pub fn max_value_size(mut self, max_bytes: usize) -> Self {
+        self.source.set_max_value_size(max_bytes);
+        self
+    }
+
+    /// See [`VaultSource::add_key_rename`].
+    pub fn rename_key(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.source.add_key_rename(from, to);
+        self
+    }
+
+    /// See [`VaultSource::set_key_case`].
+    pub fn key_case(mut self, case: KeyCase) -> Self {
+        self.source.set_key_case(case);
+        self
+    }
+
+    /// See [`VaultSource::set_text_policy`].
+    pub fn text_policy(mut self, policy: TextPolicy) -> Self {
+        self.source.set_text_policy(policy);
+        self
+    }
+
+    /// See [`VaultSource::set_report_callback`].
+    pub fn report_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&LoadReport) + Send + Sync + 'static,
+    {
+        self.source.set_report_callback(callback);
+        self
+    }
+
+    /// See [`VaultSource::set_custom_endpoint`].
+    pub fn custom_endpoint<F>(mut self, path: impl Into<String>, extract: F) -> Self
+    where
+        F: Fn(&JsonValue) -> Result<serde_json::Map<String, JsonValue>, ConfigError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.source.set_custom_endpoint(path, extract);
+        self
+    }
+
+    /// See [`VaultSource::add_audit_header`].
+    pub fn audit_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.source.add_audit_header(name, value);
+        self
+    }
+
+    /// See [`VaultSource::set_event_log`].
+    pub fn event_log(mut self, log: EventLog) -> Self {
+        self.source.set_event_log(log);
+        self
+    }
+
+    /// See [`VaultSource::set_startup_jitter`].
+    pub fn startup_jitter(mut self, max_delay: Duration) -> Self {
+        self.source.set_startup_jitter(max_delay);
+        self
+    }
+
+    /// Validates the configuration and creates the `VaultSource`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<VaultSource, ConfigError>` - The source, or an error if a required
+    ///   setting is missing or the Vault address is not a valid base URL
+    pub fn build(self) -> Result<VaultSource, ConfigError> {
+        let source = self.source;
+
+        #[cfg(feature = "blocking")]
+        let has_auth = source.auth.is_some();
+        #[cfg(not(feature = "blocking"))]
+        let has_auth = false;
+
+        if source.vault_token.is_empty() && !has_auth {
+            return Err(ConfigError::Message(
+                "VaultSource needs either a token or an auth method".into(),
+            ));
+        }
+
+        let mut required = vec![("addr", &source.vault_addr)];
+        // A custom endpoint replaces the KV mount and path; other engines may not use both.
+        if source.custom_endpoint.is_none() {
+            if source.engine.uses_mount() {
+                required.push(("mount", &source.vault_mount));
+            }
+            if source.engine.uses_path() {
+                required.push(("path", &source.vault_path));
+            }
+        }
+
+        for (name, value) in required {
+            if value.is_empty() {
+                return Err(ConfigError::Message(format!(
+                    "VaultSource is missing required setting '{}'",
+                    name
+                )));
+            }
+        }
+
+        match (&source.flatten_separator, &source.json_key) {
+            (Some(separator), _) if separator.is_empty() => {
+                return Err(ConfigError::Message(
+                    "VaultSource flatten separator must not be empty".into(),
+                ))
+            }
+            (Some(_), Some(_)) => {
+                return Err(ConfigError::Message(
+                    "VaultSource flatten separator and JSON key are mutually exclusive".into(),
+                ))
+            }
+            _ => {}
+        }
+
+        if let Some(parent) = &source.nested_at {
+            if parent.split('.').any(str::is_empty) {
+                return Err(ConfigError::Message(format!(
+                    "VaultSource parent key '{}' has an empty segment",
+                    parent
+                )));
+            }
+        }
+
+        let url = Url::parse(&source.vault_addr)
+            .map_err(|e| ConfigError::Message(format!("Invalid Vault address URL: {}", e)))?;
+        if url.cannot_be_a_base() {
+            return Err(diagnostics::not_a_base(&url));
+        }
+
+        tls::load_ca_certificates(&source.ca_certificates)?;
+        source.proxy.proxy()?;
+        if let Some(identity) = &source.client_identity {
+            identity.load()?;
+        }
+
+        Ok(source)
+    }
+}
@@ -0,0 +1,44 @@
+//! Conversion of secret JSON into typed configuration values.
+
+use config::{Map, Value, ValueKind};
+use serde_json::{Number, Value as JsonValue};
+
+/// Converts a JSON value of a secret into the `config` value of the same type.
+///
+/// Booleans, integers and floats keep their type, `null` becomes nil, objects become
+/// tables and arrays become arrays. Integers outside the `i64` and `u64` ranges are
+/// floats, or, with the `arbitrary-precision` feature, strings exactly as Vault
+/// returned them, like every non-integer number.
+pub(crate) fn to_value(value: &JsonValue) -> Value {
+    let kind = match value {
+        JsonValue::Null => ValueKind::Nil,
+        JsonValue::Bool(b) => ValueKind::Boolean(*b),
+        JsonValue::Number(n) => number_kind(n),
+        JsonValue::String(s) => ValueKind::String(s.clone()),
+        JsonValue::Array(items) => ValueKind::Array(items.iter().map(to_value).collect()),
+        JsonValue::Object(obj) => ValueKind::Table(to_table(obj)),
+    };
+    Value::new(None, kind)
+}
+
+/// Converts the top-level object of a secret into a table of typed values.
+pub(crate) fn to_table(secret: &serde_json::Map<String, JsonValue>) -> Map<String, Value> {
+    secret
+        .iter()
+        .map(|(key, value)| (key.clone(), to_value(value)))
+        .collect()
+}
+
+fn number_kind(n: &Number) -> ValueKind {
+    if let Some(i) = n.as_i64() {
+        return ValueKind::I64(i);
+    }
+    if let Some(u) = n.as_u64() {
+        return ValueKind::U64(u);
+    }
+    if cfg!(feature = "arbitrary-precision") {
+        return ValueKind::String(n.to_string());
+    }
+    n.as_f64()
+        .map_or_else(|| ValueKind::String(n.to_string()), ValueKind::Float)
+}
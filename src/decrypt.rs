@@ -0,0 +1,236 @@
+//! Decryption of client-side encrypted secret values at read time.
+//!
+//! Teams that encrypt values before writing them to Vault (envelope encryption with
+//! a KMS, age, or Vault's own transit engine) can register a [`Decryptor`] for the
+//! prefix their ciphertexts start with. While loading, every string value of the
+//! secret, nested ones included, that starts with a registered prefix is replaced by
+//! the plaintext the decryptor returns. [`TransitDecryptor`] decrypts transit
+//! ciphertexts (`vault:v1:...`).
+//!
+//! Decryptors are blocking, so this module requires the `blocking` feature; the
+//! `AsyncSource` implementation runs them on tokio's blocking thread pool.
+
+use std::fmt;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use config::ConfigError;
+use reqwest::blocking::Client;
+use serde_json::{json, Value as JsonValue};
+
+use crate::{EngineKind, VaultSource};
+
+/// Decrypts values of a secret.
+///
+/// # Example
+///
+/// ```
+/// use config::ConfigError;
+/// use config_vault::decrypt::{DecryptClient, Decryptor};
+/// use config_vault::VaultSource;
+///
+/// /// Decrypts values written as `rot13:<text>`.
+/// #[derive(Debug)]
+/// struct Rot13;
+///
+/// impl Decryptor for Rot13 {
+///     fn decrypt(&self, value: &str, _: &DecryptClient<'_>) -> Result<String, ConfigError> {
+///         Ok(value
+///             .trim_start_matches("rot13:")
+///             .chars()
+///             .map(|c| match c {
+///                 'a'..='m' | 'A'..='M' => (c as u8 + 13) as char,
+///                 'n'..='z' | 'N'..='Z' => (c as u8 - 13) as char,
+///                 _ => c,
+///             })
+///             .collect())
+///     }
+/// }
+///
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .token("hvs.EXAMPLE_TOKEN")
+///     .mount("secret")
+///     .path("dev")
+///     .decryptor("rot13:", Box::new(Rot13))
+///     .build()
+///     .unwrap();
+/// ```
+pub trait Decryptor: fmt::Debug + Send + Sync {
+    /// Returns the plaintext of `value`, the whole stored value including the prefix
+    /// the decryptor was registered for.
+    fn decrypt(&self, value: &str, client: &DecryptClient<'_>) -> Result<String, ConfigError>;
+}
+
+/// A decryptor registered for the values starting with `prefix`.
+#[derive(Debug, Clone)]
+pub(crate) struct PrefixDecryptor {
+    pub(crate) prefix: String,
+    pub(crate) decryptor: Arc<dyn Decryptor>,
+}
+
+/// Vault access handed to a [`Decryptor`], authenticated with the token of the read.
+pub struct DecryptClient<'a> {
+    source: &'a VaultSource,
+    client: &'a Client,
+    token: &'a str,
+}
+
+impl DecryptClient<'_> {
+    /// Returns the underlying HTTP client, e.g. to call an external KMS.
+    pub fn http(&self) -> &Client {
+        self.client
+    }
+
+    /// Posts `body` to `v1/<path>` with the token of the read and parses the result.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - API path below `v1/` (e.g. "transit/decrypt/app")
+    /// * `body` - JSON body of the request
+    pub fn write(&self, path: &str, body: &JsonValue) -> Result<JsonValue, ConfigError> {
+        let url = self.source.build_api_url(&format!("v1/{}", path))?;
+        self.source
+            .send(self.client.post(url).json(body), Some(self.token))
+    }
+}
+
+impl fmt::Debug for DecryptClient<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecryptClient")
+            .field("addr", &self.source.vault_addr)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Decrypts ciphertexts of Vault's transit engine with `<mount>/decrypt/<key>`.
+///
+/// Register it for the `vault:v` prefix transit ciphertexts start with. The token
+/// needs `update` on the decrypt path, and the plaintext must be UTF-8 text.
+///
+/// # Example
+///
+/// ```
+/// use config_vault::decrypt::TransitDecryptor;
+/// use config_vault::VaultSource;
+///
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .token("hvs.EXAMPLE_TOKEN")
+///     .mount("secret")
+///     .path("dev")
+///     .decryptor("vault:v", Box::new(TransitDecryptor::new("app")))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransitDecryptor {
+    mount: String,
+    key: String,
+}
+
+impl TransitDecryptor {
+    /// Decrypts with the transit key `key` on the default `transit` mount.
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            mount: "transit".to_string(),
+            key: key.into(),
+        }
+    }
+
+    /// Uses a transit mount other than `transit`.
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+}
+
+impl Decryptor for TransitDecryptor {
+    fn decrypt(&self, value: &str, client: &DecryptClient<'_>) -> Result<String, ConfigError> {
+        let response = client.write(
+            &format!("{}/decrypt/{}", self.mount, self.key),
+            &json!({ "ciphertext": value }),
+        )?;
+        let plaintext = response
+            .pointer("/data/plaintext")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                ConfigError::Message("Transit decrypt response has no plaintext".into())
+            })?;
+        let bytes = BASE64
+            .decode(plaintext)
+            .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+        String::from_utf8(bytes)
+            .map_err(|_| ConfigError::Message("Transit plaintext is not valid UTF-8".into()))
+    }
+}
+
+impl VaultSource {
+    /// Decrypts the values of the secret in a read response that start with the
+    /// prefix of a registered decryptor.
+    pub(crate) fn decrypt_values(
+        &self,
+        raw: &mut JsonValue,
+        client: &Client,
+        token: &str,
+    ) -> Result<(), ConfigError> {
+        if self.decryptors.is_empty() {
+            return Ok(());
+        }
+        let pointer = match (&self.custom_endpoint, &self.engine) {
+            (Some(_), _) => "",
+            (None, EngineKind::Kv2) => "/data/data",
+            (None, _) => "/data",
+        };
+        // A response without data is reported when it is processed.
+        let Some(JsonValue::Object(data)) = raw.pointer_mut(pointer) else {
+            return Ok(());
+        };
+
+        let client = DecryptClient {
+            source: self,
+            client,
+            token,
+        };
+        for (key, value) in data.iter_mut() {
+            self.decrypt_value(key, value, &client)?;
+        }
+        Ok(())
+    }
+
+    fn decrypt_value(
+        &self,
+        key: &str,
+        value: &mut JsonValue,
+        client: &DecryptClient<'_>,
+    ) -> Result<(), ConfigError> {
+        match value {
+            JsonValue::String(s) => {
+                let Some(entry) = self.decryptors.iter().find(|d| s.starts_with(&d.prefix)) else {
+                    return Ok(());
+                };
+                *s = entry.decryptor.decrypt(s, client).map_err(|e| {
+                    ConfigError::Message(format!(
+                        "Cannot decrypt '{}' in Vault secret {}: {}",
+                        key,
+                        self.secret_path(),
+                        e
+                    ))
+                })?;
+            }
+            JsonValue::Object(obj) => {
+                for (child, value) in obj.iter_mut() {
+                    self.decrypt_value(&format!("{}.{}", key, child), value, client)?;
+                }
+            }
+            JsonValue::Array(items) => {
+                for (index, value) in items.iter_mut().enumerate() {
+                    self.decrypt_value(&format!("{}[{}]", key, index), value, client)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+//! Actionable diagnostics for requests that never reached Vault, or reached no
+//! secrets engine.
+//!
+//! reqwest reports connection failures tersely ("error sending request"). When a
+//! request fails before Vault answers, the error is wrapped in a [`ConnectionError`]
+//! that adds what can be checked locally: the address scheme, DNS resolution, the TLS
+//! handshake and the proxy settings in effect.
+//!
+//! A read from a mount that does not exist is answered with a 404, like a missing
+//! secret, but with a "no handler for route" error; it is reported as a
+//! [`MountNotFound`].
+
+use std::error::Error;
+use std::fmt;
+use std::net::ToSocketAddrs;
+
+use config::ConfigError;
+use url::{Host, Url};
+
+use crate::proxy::ProxySetting;
+
+/// A request to Vault that failed before a response was received.
+///
+/// Returned inside `ConfigError::Foreign`; downcast it to read the diagnostics:
+///
+/// ```
+/// use config::ConfigError;
+/// use config_vault::diagnostics::ConnectionError;
+///
+/// fn print_diagnostics(error: &ConfigError) {
+///     if let ConfigError::Foreign(error) = error {
+///         if let Some(error) = error.downcast_ref::<ConnectionError>() {
+///             for hint in error.diagnostics() {
+///                 eprintln!("  - {}", hint);
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ConnectionError {
+    error: reqwest::Error,
+    diagnostics: Vec<String>,
+}
+
+impl ConnectionError {
+    /// Findings about the failure, most relevant first.
+    pub fn diagnostics(&self) -> &[String] {
+        &self.diagnostics
+    }
+
+    /// The underlying HTTP client error.
+    pub fn reqwest_error(&self) -> &reqwest::Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cannot reach Vault: {}", self.error)?;
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n  - {}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ConnectionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A read that Vault routed to no secrets engine: nothing is mounted at the mount of
+/// the secret, usually because the mount name is misspelled or belongs to another
+/// namespace.
+///
+/// Returned inside `ConfigError::Foreign`, instead of the error for a missing secret:
+///
+/// ```
+/// use config::ConfigError;
+/// use config_vault::diagnostics::MountNotFound;
+///
+/// fn is_missing_mount(error: &ConfigError) -> bool {
+///     matches!(error, ConfigError::Foreign(error) if error.is::<MountNotFound>())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountNotFound {
+    mount: String,
+    namespace: Option<String>,
+}
+
+impl MountNotFound {
+    /// The mount the secret was read from.
+    pub fn mount(&self) -> &str {
+        &self.mount
+    }
+
+    /// The namespace of the read, if one was set.
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+}
+
+impl fmt::Display for MountNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No secrets engine is mounted at '{}/'", self.mount)?;
+        if let Some(namespace) = &self.namespace {
+            write!(f, " in namespace '{}'", namespace)?;
+        }
+        write!(f, "; check the mount name against `vault secrets list`")
+    }
+}
+
+impl Error for MountNotFound {}
+
+/// Converts a 404 read response into a [`MountNotFound`] error if Vault had no route
+/// for the request, which means the mount does not exist.
+pub(crate) fn missing_mount(
+    body: &[u8],
+    mount: &str,
+    namespace: Option<&str>,
+) -> Option<ConfigError> {
+    let errors = serde_json::from_slice::<serde_json::Value>(body).ok()?;
+    let no_route = errors
+        .get("errors")?
+        .as_array()?
+        .iter()
+        .filter_map(|e| e.as_str())
+        .any(|e| e.contains("no handler for route"));
+    no_route.then(|| {
+        ConfigError::Foreign(Box::new(MountNotFound {
+            mount: mount.to_string(),
+            namespace: namespace.map(str::to_string),
+        }))
+    })
+}
+
+/// Converts an error returned by `send` into a `ConfigError`, diagnosing failures to
+/// connect. Looking up the host in DNS blocks.
+pub(crate) fn send_error(error: reqwest::Error, proxy: &ProxySetting) -> ConfigError {
+    if !(error.is_connect() || error.is_timeout() || error.is_request()) {
+        return ConfigError::Foreign(Box::new(error));
+    }
+
+    let diagnostics = diagnose(&error, proxy);
+    ConfigError::Foreign(Box::new(ConnectionError { error, diagnostics }))
+}
+
+fn diagnose(error: &reqwest::Error, proxy: &ProxySetting) -> Vec<String> {
+    let mut diagnostics = Vec::new();
+    let chain = error_chain(error).to_lowercase();
+
+    if error.is_timeout() {
+        diagnostics.push(
+            "The request timed out; check that Vault is up and that no firewall drops the \
+             connection, or raise the timeout"
+                .to_string(),
+        );
+    }
+
+    if let Some(url) = error.url() {
+        check_scheme(url, &chain, &mut diagnostics);
+        check_dns(url, &mut diagnostics);
+    }
+
+    if chain.contains("certificate") {
+        diagnostics.push(
+            "The TLS handshake failed verifying the server certificate; if Vault uses a \
+             private CA, the CA is not trusted by this client"
+                .to_string(),
+        );
+    } else if ["tls", "ssl", "handshake"]
+        .iter()
+        .any(|s| chain.contains(s))
+        && !plaintext_reply(chain.as_str())
+    {
+        diagnostics.push("The TLS handshake with the server failed".to_string());
+    } else if chain.contains("connection refused") {
+        diagnostics.push(
+            "The host refused the connection; check the port and that Vault is listening"
+                .to_string(),
+        );
+    }
+
+    diagnostics.extend(proxy.describe());
+
+    diagnostics
+}
+
+/// Error for a Vault address that cannot carry an API path, typically `host:port`
+/// without a scheme, which parses as the scheme `host`.
+pub(crate) fn not_a_base(url: &Url) -> ConfigError {
+    ConfigError::Message(format!(
+        "Vault address URL cannot be a base (parsed scheme '{}'); addresses must start \
+         with http:// or https://",
+        url.scheme()
+    ))
+}
+
+/// Flags addresses that are likely a typo of `http://` or `https://`.
+fn check_scheme(url: &Url, chain: &str, diagnostics: &mut Vec<String>) {
+    match url.scheme() {
+        "https" if plaintext_reply(chain) => diagnostics.push(
+            "The server did not answer with TLS; if Vault listens without TLS, use an \
+             http:// address"
+                .to_string(),
+        ),
+        "http" | "https" => {}
+        scheme => diagnostics.push(format!(
+            "The Vault address has the unexpected scheme '{}'; addresses must start with \
+             http:// or https:// (e.g. VAULT_ADDR=https://vault.example.com:8200)",
+            scheme
+        )),
+    }
+}
+
+/// Whether the TLS library got a non-TLS reply, as from an HTTP server (OpenSSL says
+/// "wrong version number", rustls "InvalidContentType").
+fn plaintext_reply(chain: &str) -> bool {
+    chain.contains("wrong version number") || chain.contains("invalidcontenttype")
+}
+
+/// Reports what the host of `url` resolves to.
+fn check_dns(url: &Url, diagnostics: &mut Vec<String>) {
+    let Some(Host::Domain(host)) = url.host() else {
+        return;
+    };
+    let port = url.port_or_known_default().unwrap_or(8200);
+
+    match (host, port).to_socket_addrs() {
+        Ok(addrs) => {
+            let addrs: Vec<String> = addrs.map(|addr| addr.ip().to_string()).collect();
+            if addrs.is_empty() {
+                diagnostics.push(format!("DNS returned no addresses for '{}'", host));
+            } else {
+                diagnostics.push(format!("'{}' resolves to {}", host, addrs.join(", ")));
+            }
+        }
+        Err(e) => diagnostics.push(format!("DNS resolution of '{}' failed: {}", host, e)),
+    }
+}
+
+/// Returns the message of the innermost source of an error. reqwest wraps the actual
+/// reason of builder errors, which only say "builder error".
+pub(crate) fn root_cause(error: &dyn Error) -> String {
+    let mut error = error;
+    while let Some(source) = error.source() {
+        error = source;
+    }
+    error.to_string()
+}
+
+/// Concatenates the messages of an error and all of its sources.
+fn error_chain(error: &dyn Error) -> String {
+    let mut chain = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        chain.push_str(": ");
+        chain.push_str(&error.to_string());
+        source = error.source();
+    }
+    chain
+}
@@ -0,0 +1,139 @@
+//! Structured key diffs between two configuration sources.
+//!
+//! Comparing the same relative path in two environments (or namespaces) catches a
+//! key that production is missing but development has before it breaks a deploy.
+//! Only key names are compared, so no secret values are exposed by a diff.
+
+use std::collections::BTreeSet;
+
+use config::{Config, ConfigError, Map, Source, Value, ValueKind};
+
+use crate::CollectedSource;
+
+/// Keys present in one side of a comparison but not the other.
+///
+/// Nested tables are compared by their dotted key paths (e.g. `database.password`).
+/// Dotted keys in a collected map, as produced by
+/// [`VaultSource::set_nested_at`](crate::VaultSource::set_nested_at), are compared as
+/// the nested keys `config` turns them into.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyDiff {
+    /// Keys only present in the left-hand side, sorted.
+    pub only_in_left: Vec<String>,
+    /// Keys only present in the right-hand side, sorted.
+    pub only_in_right: Vec<String>,
+}
+
+impl KeyDiff {
+    /// Compares the keys of two collected maps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use config::{Map, Value};
+    /// use config_vault::diff::KeyDiff;
+    ///
+    /// let mut dev = Map::new();
+    /// dev.insert("db_user".to_string(), Value::from("app"));
+    /// dev.insert("feature_flag".to_string(), Value::from("on"));
+    /// let mut prod = Map::new();
+    /// prod.insert("db_user".to_string(), Value::from("app"));
+    ///
+    /// let diff = KeyDiff::between(&dev, &prod);
+    /// assert_eq!(diff.only_in_left, vec!["feature_flag".to_string()]);
+    /// assert!(diff.only_in_right.is_empty());
+    ///
+    /// // A dotted key and the same key in a nested table are the same key.
+    /// let mut database = Map::new();
+    /// database.insert("user".to_string(), Value::from("app"));
+    /// let mut nested = Map::new();
+    /// nested.insert("database".to_string(), Value::from(database));
+    /// let mut dotted = Map::new();
+    /// dotted.insert("database.user".to_string(), Value::from("app"));
+    /// assert!(KeyDiff::between(&nested, &dotted).is_empty());
+    /// ```
+    pub fn between(left: &Map<String, Value>, right: &Map<String, Value>) -> Self {
+        let left = key_paths(left, false);
+        let right = key_paths(right, false);
+
+        Self {
+            only_in_left: left.difference(&right).cloned().collect(),
+            only_in_right: right.difference(&left).cloned().collect(),
+        }
+    }
+
+    /// Returns `true` if both sides have exactly the same keys.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_left.is_empty() && self.only_in_right.is_empty()
+    }
+}
+
+/// Collects both sources and compares their keys.
+///
+/// # Example
+///
+/// ```no_run
+/// use config_vault::{diff, VaultSource};
+///
+/// let dev = VaultSource::new(
+///     "https://vault.dev.example.com",
+///     "hvs.DEV_TOKEN",
+///     "secret",
+///     "app",
+/// );
+/// let prod = VaultSource::new(
+///     "https://vault.prod.example.com",
+///     "hvs.PROD_TOKEN",
+///     "secret",
+///     "app",
+/// );
+///
+/// let diff = diff::diff_keys(&dev, &prod)?;
+/// for key in &diff.only_in_left {
+///     println!("prod is missing {}", key);
+/// }
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+pub fn diff_keys(left: &dyn Source, right: &dyn Source) -> Result<KeyDiff, ConfigError> {
+    Ok(KeyDiff::between(&left.collect()?, &right.collect()?))
+}
+
+/// Rebuilds `values` the way `config` stores a source: dotted keys and `[index]`
+/// suffixes become nested tables and arrays.
+pub(crate) fn expand_keys(values: &Map<String, Value>) -> Map<String, Value> {
+    Config::builder()
+        .add_source(CollectedSource::new(values.clone()))
+        .build()
+        .and_then(|config| config.collect())
+        .unwrap_or_else(|_| values.clone())
+}
+
+/// Collects the dotted paths of the leaves of `values`, after expanding dotted keys.
+///
+/// With `into_arrays`, array items are paths of their own (`servers[0].host`);
+/// otherwise an array is a leaf, so that a list growing does not add keys.
+pub(crate) fn key_paths(values: &Map<String, Value>, into_arrays: bool) -> BTreeSet<String> {
+    let mut paths = BTreeSet::new();
+    for (key, value) in expand_keys(values) {
+        collect_paths(&mut paths, key, &value, into_arrays);
+    }
+    paths
+}
+
+fn collect_paths(paths: &mut BTreeSet<String>, prefix: String, value: &Value, into_arrays: bool) {
+    match &value.kind {
+        ValueKind::Table(table) if !table.is_empty() => {
+            for (key, value) in table {
+                collect_paths(paths, format!("{}.{}", prefix, key), value, into_arrays);
+            }
+        }
+        ValueKind::Array(items) if into_arrays && !items.is_empty() => {
+            for (index, item) in items.iter().enumerate() {
+                collect_paths(paths, format!("{}[{}]", prefix, index), item, into_arrays);
+            }
+        }
+        _ => {
+            paths.insert(prefix);
+        }
+    }
+}
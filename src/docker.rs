@@ -0,0 +1,81 @@
+//! Rendering of registry credentials in the Docker credential-helper protocol.
+//!
+//! A `docker-credential-<name>` helper answers the `get` command by printing a JSON
+//! document with the registry URL, the user name and the secret. [`DockerCredentials`]
+//! builds that document from values loaded out of Vault.
+
+use config::{ConfigError, Map, Value};
+use serde_json::json;
+
+/// Registry credentials as exchanged with the Docker credential-helper protocol.
+///
+/// # Example
+///
+/// ```
+/// use config::{Map, Value};
+/// use config_vault::docker::DockerCredentials;
+///
+/// let mut secret = Map::new();
+/// secret.insert("username".to_string(), Value::from("ci-bot"));
+/// secret.insert("password".to_string(), Value::from("s3cr3t"));
+///
+/// let credentials =
+///     DockerCredentials::from_map("registry.example.com", &secret, "username", "password")
+///         .unwrap();
+///
+/// let rendered: serde_json::Value = serde_json::from_str(&credentials.to_json()).unwrap();
+/// assert_eq!(rendered["ServerURL"], "registry.example.com");
+/// assert_eq!(rendered["Username"], "ci-bot");
+/// assert_eq!(rendered["Secret"], "s3cr3t");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DockerCredentials {
+    pub server_url: String,
+    pub username: String,
+    pub secret: String,
+}
+
+impl DockerCredentials {
+    /// Extracts the credentials for `server_url` from a collected secret.
+    ///
+    /// # Parameters
+    ///
+    /// * `server_url` - Registry the credentials belong to (e.g. "registry.example.com")
+    /// * `secret` - Values returned by `VaultSource::collect`
+    /// * `username_key` - Key holding the registry user name
+    /// * `secret_key` - Key holding the registry password or token
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DockerCredentials, ConfigError>` - The credentials or an error if a key is missing
+    pub fn from_map(
+        server_url: impl Into<String>,
+        secret: &Map<String, Value>,
+        username_key: &str,
+        secret_key: &str,
+    ) -> Result<Self, ConfigError> {
+        let get = |key: &str| {
+            secret
+                .get(key)
+                .cloned()
+                .ok_or_else(|| ConfigError::NotFound(key.to_string()))?
+                .into_string()
+        };
+
+        Ok(Self {
+            server_url: server_url.into(),
+            username: get(username_key)?,
+            secret: get(secret_key)?,
+        })
+    }
+
+    /// Renders the credentials as the JSON answer to the helper `get` command.
+    pub fn to_json(&self) -> String {
+        json!({
+            "ServerURL": self.server_url,
+            "Username": self.username,
+            "Secret": self.secret,
+        })
+        .to_string()
+    }
+}
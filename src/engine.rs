@@ -0,0 +1,128 @@
+//! Secrets engines a [`VaultSource`](crate::VaultSource) can read from.
+
+use std::fmt;
+use std::str::FromStr;
+
+use config::ConfigError;
+use serde::Deserialize;
+
+#[allow(deprecated)]
+use crate::KvVersion;
+
+/// The secrets engine a secret is read from, which determines its API path and where
+/// the data sits in the response.
+///
+/// Parses from and displays as `"kv1"`, `"kv2"`, `"cubbyhole"` or a path template,
+/// so the engine can be chosen in a settings file (`"v1"` and `"v2"` are accepted as
+/// aliases of the KV versions). A template is the API path with `{mount}` and `{path}`
+/// placeholders, e.g. `"v1/{mount}/creds/{path}"`; its response data is read from the
+/// `data` field, as for KV1.
+///
+/// # Example
+///
+/// ```
+/// use config_vault::{EngineKind, VaultSource};
+///
+/// let engine: EngineKind = "v1/{mount}/creds/{path}".parse().unwrap();
+/// assert_eq!(engine.to_string(), "v1/{mount}/creds/{path}");
+///
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .token("hvs.EXAMPLE_TOKEN")
+///     .engine(EngineKind::Cubbyhole)
+///     .path("bootstrap")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum EngineKind {
+    /// KV version 1: `v1/<mount>/<path>`.
+    Kv1,
+    /// KV version 2: `v1/<mount>/data/<path>`, with the data nested in `data.data`.
+    #[default]
+    Kv2,
+    /// The token's cubbyhole: `v1/cubbyhole/<path>`. No mount is needed.
+    Cubbyhole,
+    /// An API path template with `{mount}` and `{path}` placeholders.
+    Custom(String),
+}
+
+impl EngineKind {
+    /// Returns the API path of the secret at `path` in `mount`.
+    pub(crate) fn api_path(&self, mount: &str, path: &str) -> String {
+        match self {
+            EngineKind::Kv1 => format!("v1/{}/{}", mount, path),
+            EngineKind::Kv2 => format!("v1/{}/data/{}", mount, path),
+            EngineKind::Cubbyhole => format!("v1/cubbyhole/{}", path),
+            EngineKind::Custom(template) => template
+                .trim_start_matches('/')
+                .replace("{mount}", mount)
+                .replace("{path}", path),
+        }
+    }
+
+    /// Whether the API path depends on the mount.
+    pub(crate) fn uses_mount(&self) -> bool {
+        match self {
+            EngineKind::Kv1 | EngineKind::Kv2 => true,
+            EngineKind::Cubbyhole => false,
+            EngineKind::Custom(template) => template.contains("{mount}"),
+        }
+    }
+
+    /// Whether the API path depends on the path.
+    pub(crate) fn uses_path(&self) -> bool {
+        match self {
+            EngineKind::Custom(template) => template.contains("{path}"),
+            _ => true,
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<KvVersion> for EngineKind {
+    fn from(version: KvVersion) -> Self {
+        match version {
+            KvVersion::V1 => EngineKind::Kv1,
+            KvVersion::V2 => EngineKind::Kv2,
+        }
+    }
+}
+
+impl FromStr for EngineKind {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kv1" | "v1" => Ok(EngineKind::Kv1),
+            "kv2" | "v2" => Ok(EngineKind::Kv2),
+            "cubbyhole" => Ok(EngineKind::Cubbyhole),
+            template if template.contains('/') => Ok(EngineKind::Custom(template.to_string())),
+            other => Err(ConfigError::Message(format!(
+                "Unknown secrets engine '{}'; expected kv1, kv2, cubbyhole or a path \
+                 template such as v1/{{mount}}/creds/{{path}}",
+                other
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for EngineKind {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for EngineKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineKind::Kv1 => f.write_str("kv1"),
+            EngineKind::Kv2 => f.write_str("kv2"),
+            EngineKind::Cubbyhole => f.write_str("cubbyhole"),
+            EngineKind::Custom(template) => f.write_str(template),
+        }
+    }
+}
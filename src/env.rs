@@ -0,0 +1,273 @@
+//! Construction of a [`VaultSource`] from the Vault CLI environment variables.
+
+use config::ConfigError;
+
+#[cfg(feature = "blocking")]
+use crate::auth::CliTokenAuth;
+use crate::VaultSource;
+
+/// Address the Vault CLI uses when `VAULT_ADDR` is not set.
+const DEFAULT_ADDR: &str = "https://127.0.0.1:8200";
+
+impl VaultSource {
+    /// Creates a `VaultSource` configured like the Vault CLI in the current environment.
+    ///
+    /// The address is read from `VAULT_ADDR`, defaulting to `https://127.0.0.1:8200`
+    /// like the CLI, the namespace from `VAULT_NAMESPACE`, the trusted CAs from
+    /// `VAULT_CACERT` and `VAULT_CAPATH` and the client certificate for mutual TLS from
+    /// `VAULT_CLIENT_CERT` and `VAULT_CLIENT_KEY`. `VAULT_SKIP_VERIFY` disables
+    /// certificate verification (see [`VaultSource::set_danger_accept_invalid_certs`]).
+    /// `VAULT_PROXY_ADDR` (or the older `VAULT_HTTP_PROXY`) sets a proxy. The token is
+    /// resolved with [`CliTokenAuth`]: `VAULT_TOKEN`, then the token helper or
+    /// `~/.vault-token`, at every `collect`. Without the `blocking` feature only
+    /// `VAULT_TOKEN` is read.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<VaultSource, ConfigError>` - The source, or an error if the address is
+    ///   invalid or a variable cannot be parsed
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use config::Config;
+    /// use config_vault::VaultSource;
+    ///
+    /// let config = Config::builder()
+    ///     .add_source(VaultSource::from_env("secret", "dev").unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn from_env(
+        mount: impl Into<String>,
+        path: impl Into<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut builder = VaultSource::builder()
+            .addr(env_var("VAULT_ADDR").unwrap_or_else(|| DEFAULT_ADDR.to_string()))
+            .mount(mount)
+            .path(path);
+        if let Some(namespace) = env_var("VAULT_NAMESPACE") {
+            builder = builder.namespace(namespace);
+        }
+        if let Some(path) = env_var("VAULT_CACERT") {
+            builder = builder.ca_certificate_file(path);
+        }
+        if let Some(path) = env_var("VAULT_CAPATH") {
+            builder = builder.ca_directory(path);
+        }
+        if let Some(skip) = env_var("VAULT_SKIP_VERIFY") {
+            builder = builder.danger_accept_invalid_certs(parse_bool("VAULT_SKIP_VERIFY", &skip)?);
+        }
+        if let Some(url) = env_var("VAULT_PROXY_ADDR").or_else(|| env_var("VAULT_HTTP_PROXY")) {
+            builder = builder.proxy(url);
+        }
+        match (env_var("VAULT_CLIENT_CERT"), env_var("VAULT_CLIENT_KEY")) {
+            (Some(cert), Some(key)) => builder = builder.client_certificate_files(cert, key),
+            (None, None) => {}
+            _ => {
+                return Err(ConfigError::Message(
+                    "VAULT_CLIENT_CERT and VAULT_CLIENT_KEY must be set together".into(),
+                ))
+            }
+        }
+
+        #[cfg(feature = "blocking")]
+        let builder = builder.auth(Box::new(CliTokenAuth::new()));
+        #[cfg(not(feature = "blocking"))]
+        let builder = builder.token(
+            env_var("VAULT_TOKEN").ok_or_else(|| ConfigError::NotFound("VAULT_TOKEN".into()))?,
+        );
+
+        builder.build()
+    }
+}
+
+/// Parses a boolean like Go's `strconv.ParseBool`, which the CLI uses.
+fn parse_bool(name: &str, value: &str) -> Result<bool, ConfigError> {
+    match value {
+        "1" | "t" | "T" | "TRUE" | "true" | "True" => Ok(true),
+        "0" | "f" | "F" | "FALSE" | "false" | "False" => Ok(false),
+        _ => Err(ConfigError::Message(format!(
+            "{} must be a boolean, got '{}'",
+            name, value
+        ))),
+    }
+}
+
+/// Reads an environment variable, treating an empty value as unset like the CLI.
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::path::Path;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    use super::*;
+    use crate::proxy::ProxySetting;
+    use crate::tls::{CaSource, ClientIdentity};
+
+    /// Variables read by `from_env` and `CliTokenAuth`.
+    const VARS: [&str; 11] = [
+        "VAULT_ADDR",
+        "VAULT_TOKEN",
+        "VAULT_NAMESPACE",
+        "VAULT_CACERT",
+        "VAULT_CAPATH",
+        "VAULT_SKIP_VERIFY",
+        "VAULT_PROXY_ADDR",
+        "VAULT_HTTP_PROXY",
+        "VAULT_CLIENT_CERT",
+        "VAULT_CLIENT_KEY",
+        "VAULT_CONFIG_PATH",
+    ];
+
+    /// Serializes the tests that change the environment of the process.
+    pub(crate) fn lock_env() -> MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs `f` with `vars` set and the other Vault variables unset, then restores
+    /// the environment.
+    pub(crate) fn with_env<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+        let _lock = lock_env();
+        let saved: Vec<(&str, Option<String>)> = VARS
+            .iter()
+            .map(|&var| (var, std::env::var(var).ok()))
+            .collect();
+        for var in VARS {
+            std::env::remove_var(var);
+        }
+        for (var, value) in vars {
+            std::env::set_var(var, value);
+        }
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+
+        for (var, value) in saved {
+            match value {
+                Some(value) => std::env::set_var(var, value),
+                None => std::env::remove_var(var),
+            }
+        }
+        result.unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+
+    /// Calls `from_env` with `vars` set, and `VAULT_TOKEN`, which builds without the
+    /// `blocking` feature require.
+    fn from_env(vars: &[(&str, &str)]) -> Result<VaultSource, ConfigError> {
+        let mut vars = vars.to_vec();
+        vars.push(("VAULT_TOKEN", "hvs.EXAMPLE_TOKEN"));
+        with_env(&vars, || VaultSource::from_env("secret", "dev"))
+    }
+
+    #[test]
+    fn defaults_match_the_cli() {
+        let source = from_env(&[]).unwrap();
+
+        assert_eq!(source.vault_addr, "https://127.0.0.1:8200");
+        assert_eq!(source.vault_mount, "secret");
+        assert_eq!(source.vault_path, "dev");
+        assert_eq!(source.namespace, None);
+        assert!(source.ca_certificates.is_empty());
+        assert!(source.client_identity.is_none());
+        assert!(!source.accept_invalid_certs);
+        assert!(matches!(source.proxy, ProxySetting::System));
+    }
+
+    #[test]
+    fn variables_map_onto_the_source() {
+        // The certificate files are loaded when the source is built.
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let ca_dir = fixtures.join("ca");
+        let cert = ca_dir.join("vault-test.pem");
+        let key = fixtures.join("client-key.pem");
+        let source = from_env(&[
+            ("VAULT_ADDR", "http://vault.test:8200"),
+            ("VAULT_NAMESPACE", "team"),
+            ("VAULT_CACERT", cert.to_str().unwrap()),
+            ("VAULT_CAPATH", ca_dir.to_str().unwrap()),
+            ("VAULT_SKIP_VERIFY", "true"),
+            ("VAULT_PROXY_ADDR", "http://proxy.test:3128"),
+            ("VAULT_HTTP_PROXY", "http://ignored.test:3128"),
+            ("VAULT_CLIENT_CERT", cert.to_str().unwrap()),
+            ("VAULT_CLIENT_KEY", key.to_str().unwrap()),
+        ])
+        .unwrap();
+
+        assert_eq!(source.vault_addr, "http://vault.test:8200");
+        assert_eq!(source.namespace.as_deref(), Some("team"));
+        assert!(matches!(
+            source.ca_certificates.as_slice(),
+            [CaSource::File(file), CaSource::Dir(dir)] if *file == cert && *dir == ca_dir
+        ));
+        assert!(source.accept_invalid_certs);
+        assert!(matches!(
+            &source.proxy,
+            ProxySetting::Url { url, credentials: None } if url == "http://proxy.test:3128"
+        ));
+        assert!(matches!(
+            &source.client_identity,
+            Some(ClientIdentity::Files { cert: c, key: k }) if *c == cert && *k == key
+        ));
+    }
+
+    #[test]
+    fn legacy_proxy_variable_is_read() {
+        let source = from_env(&[("VAULT_HTTP_PROXY", "http://proxy.test:3128")]).unwrap();
+
+        assert!(matches!(
+            &source.proxy,
+            ProxySetting::Url { url, .. } if url == "http://proxy.test:3128"
+        ));
+    }
+
+    #[test]
+    fn empty_variables_are_unset() {
+        let source = from_env(&[("VAULT_ADDR", ""), ("VAULT_NAMESPACE", "")]).unwrap();
+
+        assert_eq!(source.vault_addr, "https://127.0.0.1:8200");
+        assert_eq!(source.namespace, None);
+    }
+
+    #[test]
+    fn invalid_variables_are_errors() {
+        let error = from_env(&[("VAULT_SKIP_VERIFY", "yes")]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "VAULT_SKIP_VERIFY must be a boolean, got 'yes'"
+        );
+
+        let error = from_env(&[("VAULT_CLIENT_CERT", "/etc/vault/client.pem")]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "VAULT_CLIENT_CERT and VAULT_CLIENT_KEY must be set together"
+        );
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn token_is_read_from_vault_token_at_collect() {
+        let source = from_env(&[]).unwrap();
+        let client = reqwest::blocking::Client::new();
+
+        let token = with_env(&[("VAULT_TOKEN", "hvs.FROM_ENV")], || {
+            source.resolve_token(&client)
+        });
+        assert_eq!(token.unwrap(), "hvs.FROM_ENV");
+    }
+
+    #[cfg(not(feature = "blocking"))]
+    #[test]
+    fn token_is_read_from_vault_token() {
+        let source = with_env(&[("VAULT_TOKEN", "hvs.FROM_ENV")], || {
+            VaultSource::from_env("secret", "dev")
+        })
+        .unwrap();
+        assert_eq!(source.vault_token, "hvs.FROM_ENV");
+
+        assert!(with_env(&[], || VaultSource::from_env("secret", "dev")).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Mapping of logical environment names to Vault locations.
+//!
+//! One binary deployed to several environments usually talks to a different Vault
+//! (or namespace, or mount) in each. An [`EnvironmentTable`] declares them all in one
+//! place, and the environment the process runs in picks the row, e.g. with
+//! `APP_ENV=staging`.
+
+use std::collections::BTreeMap;
+
+use config::ConfigError;
+use serde::Deserialize;
+
+use crate::{VaultSource, VaultSourceBuilder};
+
+/// Where the secrets of one environment live.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VaultLocation {
+    /// Complete URL of the Vault server.
+    pub addr: String,
+    /// Vault Enterprise namespace; see [`VaultSource::set_namespace`].
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Name of the KV engine mount.
+    pub mount: String,
+}
+
+impl VaultLocation {
+    /// Creates a builder targeting this location. The path and the token or auth
+    /// method remain to be set.
+    pub fn builder(&self) -> VaultSourceBuilder {
+        let mut builder = VaultSource::builder()
+            .addr(self.addr.clone())
+            .mount(self.mount.clone());
+        if let Some(namespace) = &self.namespace {
+            builder = builder.namespace(namespace.clone());
+        }
+        builder
+    }
+}
+
+/// Table of [`VaultLocation`]s by environment name.
+///
+/// Deserializes from a map of environment names to locations:
+///
+/// ```
+/// use config::{Config, File, FileFormat};
+/// use config_vault::environments::EnvironmentTable;
+///
+/// let deployment = Config::builder()
+///     .add_source(File::from_str(
+///         r#"
+///         [vault.staging]
+///         addr = "https://vault.staging.example.com"
+///         mount = "secret"
+///
+///         [vault.production]
+///         addr = "https://vault.example.com"
+///         namespace = "team-a/prod"
+///         mount = "kv"
+///         "#,
+///         FileFormat::Toml,
+///     ))
+///     .build()?;
+///
+/// let table: EnvironmentTable = deployment.get("vault")?;
+/// let location = table.get("production")?;
+/// assert_eq!(location.namespace.as_deref(), Some("team-a/prod"));
+///
+/// let source = location
+///     .builder()
+///     .token("hvs.EXAMPLE_TOKEN")
+///     .path("billing")
+///     .build()?;
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct EnvironmentTable {
+    locations: BTreeMap<String, VaultLocation>,
+}
+
+impl EnvironmentTable {
+    /// Creates a table without environments.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces the location of an environment.
+    pub fn insert(&mut self, environment: impl Into<String>, location: VaultLocation) {
+        self.locations.insert(environment.into(), location);
+    }
+
+    /// Returns the location of `environment`, or an error listing the known ones.
+    pub fn get(&self, environment: &str) -> Result<&VaultLocation, ConfigError> {
+        self.locations.get(environment).ok_or_else(|| {
+            ConfigError::Message(format!(
+                "Unknown environment '{}'; the Vault environment table has: {}",
+                environment,
+                self.environments().join(", ")
+            ))
+        })
+    }
+
+    /// Returns the location of the environment named by the environment variable `var`
+    /// (e.g. `APP_ENV`).
+    pub fn from_env_var(&self, var: &str) -> Result<&VaultLocation, ConfigError> {
+        let environment = std::env::var(var)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| ConfigError::NotFound(var.to_string()))?;
+        self.get(&environment)
+    }
+
+    /// Names of the environments in the table, sorted.
+    pub fn environments(&self) -> Vec<&str> {
+        self.locations.keys().map(String::as_str).collect()
+    }
+}
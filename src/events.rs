@@ -0,0 +1,175 @@
+//! In-memory history of recent Vault interactions.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use config::ConfigError;
+
+use crate::VaultSource;
+
+/// A single interaction of a `VaultSource` with Vault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultEvent {
+    /// When the event happened.
+    pub at: SystemTime,
+    /// Mount and path of the secret (e.g. "secret/app/prod").
+    pub path: String,
+    /// What happened.
+    pub kind: EventKind,
+}
+
+/// Kind of a [`VaultEvent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    /// Logged in with the configured auth method.
+    Login,
+    /// Fetched the secret.
+    Fetched {
+        /// Number of keys loaded into the configuration.
+        keys: usize,
+        /// Version of the secret, when the engine is versioned (KV2).
+        version: Option<u64>,
+    },
+    /// A fetch failed.
+    Failed {
+        /// The error, as displayed by `ConfigError`.
+        error: String,
+    },
+}
+
+/// Ring buffer of the most recent [`VaultEvent`]s.
+///
+/// The log is a shared handle: keep a clone and pass another one to
+/// [`VaultSource::set_event_log`], then read it back from e.g. a debug endpoint.
+/// Once `capacity` events are stored, the oldest one is dropped for every new event.
+/// Events never contain secret values.
+///
+/// # Example
+///
+/// ```
+/// use config_vault::events::EventLog;
+/// use config_vault::VaultSource;
+///
+/// let events = EventLog::new(100);
+///
+/// let mut source = VaultSource::new(
+///     "http://127.0.0.1:8200",
+///     "hvs.EXAMPLE_TOKEN",
+///     "secret",
+///     "dev",
+/// );
+/// source.set_event_log(events.clone());
+///
+/// for event in events.events() {
+///     println!("{:?} {} {:?}", event.at, event.path, event.kind);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    capacity: usize,
+    events: Arc<Mutex<VecDeque<VaultEvent>>>,
+}
+
+impl EventLog {
+    /// Creates an empty log that keeps the last `capacity` events.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            events: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// Returns the stored events, oldest first.
+    pub fn events(&self) -> Vec<VaultEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Removes every stored event.
+    pub fn clear(&self) {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    fn push(&self, event: VaultEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut events = self.events.lock().unwrap_or_else(PoisonError::into_inner);
+        while events.len() >= self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+}
+
+impl VaultSource {
+    /// Records an event in the event log, if one is set.
+    pub(crate) fn record_event(&self, kind: EventKind) {
+        if let Some(log) = &self.event_log {
+            log.push(VaultEvent {
+                at: SystemTime::now(),
+                path: self.secret_path(),
+                kind,
+            });
+        }
+    }
+
+    /// Records a failed fetch in the event log, if one is set.
+    pub(crate) fn record_failure(&self, error: &ConfigError) {
+        self.record_event(EventKind::Failed {
+            error: error.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(n: usize) -> VaultEvent {
+        VaultEvent {
+            at: SystemTime::UNIX_EPOCH,
+            path: "secret/app".to_string(),
+            kind: EventKind::Failed {
+                error: n.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn oldest_events_are_dropped() {
+        let log = EventLog::new(3);
+        for n in 0..5 {
+            log.push(failure(n));
+        }
+
+        assert_eq!(log.events(), [failure(2), failure(3), failure(4)]);
+    }
+
+    #[test]
+    fn clones_share_the_events() {
+        let log = EventLog::new(3);
+        let clone = log.clone();
+        clone.push(failure(0));
+        assert_eq!(log.events(), [failure(0)]);
+
+        log.clear();
+        assert!(clone.events().is_empty());
+    }
+
+    #[test]
+    fn zero_capacity_keeps_nothing() {
+        let log = EventLog::new(0);
+        log.push(failure(0));
+
+        assert!(log.events().is_empty());
+    }
+}
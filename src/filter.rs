@@ -0,0 +1,115 @@
+//! Selection of the secret keys that are loaded.
+
+use config::{Map, Value};
+
+/// Glob patterns that select the top-level keys of a secret.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KeyFilter {
+    pub(crate) include: Vec<String>,
+    pub(crate) exclude: Vec<String>,
+}
+
+impl KeyFilter {
+    /// Drops the keys that match no include pattern (when there are any) or match an
+    /// exclude pattern.
+    pub(crate) fn retain(&self, secret: &mut Map<String, Value>) {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return;
+        }
+        secret.retain(|key, _| {
+            (self.include.is_empty() || self.include.iter().any(|p| glob_match(p, key)))
+                && !self.exclude.iter().any(|p| glob_match(p, key))
+        });
+    }
+}
+
+/// Matches `text` against a glob where `*` matches any run of characters and `?`
+/// matches a single character.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and of the text it was matched up to, to backtrack to.
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_wildcards() {
+        assert!(glob_match("db_*", "db_password"));
+        assert!(glob_match("db_*", "db_"));
+        assert!(glob_match("*_key", "api_key"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(glob_match("a*b", "abab"));
+        assert!(glob_match("key_?", "key_1"));
+        assert!(glob_match("exact", "exact"));
+
+        assert!(!glob_match("db_*", "app_db_password"));
+        assert!(!glob_match("key_?", "key_12"));
+        assert!(!glob_match("key_?", "key_"));
+        assert!(!glob_match("exact", "exactly"));
+        assert!(!glob_match("a*b", "abac"));
+        assert!(!glob_match("", "a"));
+    }
+
+    #[test]
+    fn glob_match_counts_characters_not_bytes() {
+        assert!(glob_match("contraseñ?", "contraseña"));
+        assert!(glob_match("?", "ñ"));
+    }
+
+    fn filtered(include: &[&str], exclude: &[&str]) -> Vec<String> {
+        let mut secret = Map::new();
+        for key in ["db_user", "db_password", "api_key", "region"] {
+            secret.insert(key.to_string(), Value::from("x"));
+        }
+        KeyFilter {
+            include: include.iter().map(|p| p.to_string()).collect(),
+            exclude: exclude.iter().map(|p| p.to_string()).collect(),
+        }
+        .retain(&mut secret);
+        let mut keys: Vec<String> = secret.into_keys().collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn retain_applies_includes_then_excludes() {
+        assert_eq!(filtered(&[], &[]).len(), 4);
+        assert_eq!(filtered(&["db_*"], &[]), ["db_password", "db_user"]);
+        assert_eq!(
+            filtered(&[], &["*_key", "region"]),
+            ["db_password", "db_user"]
+        );
+        assert_eq!(
+            filtered(&["db_*", "api_*"], &["*password"]),
+            ["api_key", "db_user"]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Deterministic fingerprints of collected configuration.
+//!
+//! A fingerprint is a stable BLAKE3 hash of a key/value map. Deploy tooling can compare
+//! the fingerprints of two replicas to detect configuration drift without ever
+//! exchanging the secrets themselves.
+
+use config::{Map, Value, ValueKind};
+
+use crate::diff::{expand_keys, key_paths};
+
+/// What goes into a fingerprint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FingerprintPolicy {
+    /// Only the full paths of the keys, e.g. `database.user` or `servers[0].host`;
+    /// detects added, removed or renamed keys at any depth.
+    KeysOnly,
+    /// Key names and values; detects any change.
+    KeysAndValues,
+}
+
+/// Computes the hex-encoded BLAKE3 fingerprint of `values`.
+///
+/// The result does not depend on the iteration order of the map, so replicas that
+/// loaded the same configuration always produce the same fingerprint. Dotted keys are
+/// expanded into nested tables first, as `config` does when it loads the values.
+///
+/// # Example
+///
+/// ```
+/// use config::{Map, Value};
+/// use config_vault::fingerprint::{fingerprint, FingerprintPolicy};
+///
+/// let mut a = Map::new();
+/// a.insert("user".to_string(), Value::from("app"));
+/// let mut b = a.clone();
+/// b.insert("user".to_string(), Value::from("admin"));
+///
+/// assert_eq!(
+///     fingerprint(&a, FingerprintPolicy::KeysOnly),
+///     fingerprint(&b, FingerprintPolicy::KeysOnly)
+/// );
+/// assert_ne!(
+///     fingerprint(&a, FingerprintPolicy::KeysAndValues),
+///     fingerprint(&b, FingerprintPolicy::KeysAndValues)
+/// );
+///
+/// // Renaming a nested key changes the fingerprint, even with `KeysOnly`.
+/// let mut database = Map::new();
+/// database.insert("user".to_string(), Value::from("app"));
+/// let mut c = Map::new();
+/// c.insert("database".to_string(), Value::from(database));
+/// let mut database = Map::new();
+/// database.insert("username".to_string(), Value::from("app"));
+/// let mut d = Map::new();
+/// d.insert("database".to_string(), Value::from(database));
+///
+/// assert_ne!(
+///     fingerprint(&c, FingerprintPolicy::KeysOnly),
+///     fingerprint(&d, FingerprintPolicy::KeysOnly)
+/// );
+/// ```
+pub fn fingerprint(values: &Map<String, Value>, policy: FingerprintPolicy) -> String {
+    let mut hasher = blake3::Hasher::new();
+    match policy {
+        FingerprintPolicy::KeysOnly => hash_key_paths(&mut hasher, values),
+        FingerprintPolicy::KeysAndValues => hash_table(&mut hasher, &expand_keys(values)),
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+fn hash_key_paths(hasher: &mut blake3::Hasher, values: &Map<String, Value>) {
+    let paths = key_paths(values, true);
+
+    hasher.update(&(paths.len() as u64).to_le_bytes());
+    for path in paths {
+        hash_bytes(hasher, path.as_bytes());
+    }
+}
+
+fn hash_table(hasher: &mut blake3::Hasher, table: &Map<String, Value>) {
+    let mut keys: Vec<&String> = table.keys().collect();
+    keys.sort();
+
+    hasher.update(&(keys.len() as u64).to_le_bytes());
+    for key in keys {
+        hash_bytes(hasher, key.as_bytes());
+        hash_value(hasher, &table[key]);
+    }
+}
+
+/// Hashes a value with a kind tag and length prefixes so that distinct maps never
+/// produce the same byte stream.
+fn hash_value(hasher: &mut blake3::Hasher, value: &Value) {
+    match &value.kind {
+        ValueKind::Nil => {
+            hasher.update(b"n");
+        }
+        ValueKind::Boolean(b) => {
+            hasher.update(&[b'b', u8::from(*b)]);
+        }
+        ValueKind::I64(i) => {
+            hasher.update(b"i");
+            hasher.update(&i64::to_le_bytes(*i));
+        }
+        ValueKind::I128(i) => {
+            hasher.update(b"I");
+            hasher.update(&i128::to_le_bytes(*i));
+        }
+        ValueKind::U64(u) => {
+            hasher.update(b"u");
+            hasher.update(&u64::to_le_bytes(*u));
+        }
+        ValueKind::U128(u) => {
+            hasher.update(b"U");
+            hasher.update(&u128::to_le_bytes(*u));
+        }
+        ValueKind::Float(f) => {
+            hasher.update(b"f");
+            hasher.update(&f64::to_le_bytes(*f));
+        }
+        ValueKind::String(s) => {
+            hasher.update(b"s");
+            hash_bytes(hasher, s.as_bytes());
+        }
+        ValueKind::Table(table) => {
+            hasher.update(b"t");
+            hash_table(hasher, table);
+        }
+        ValueKind::Array(items) => {
+            hasher.update(b"a");
+            hasher.update(&(items.len() as u64).to_le_bytes());
+            for item in items {
+                hash_value(hasher, item);
+            }
+        }
+    }
+}
+
+fn hash_bytes(hasher: &mut blake3::Hasher, bytes: &[u8]) {
+    hasher.update(&(bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
@@ -0,0 +1,157 @@
+//! Flattening of nested secret data into environment-style flat keys.
+
+use config::{Map, Value, ValueKind};
+use serde_json::Value as JsonValue;
+
+use crate::validation::KeyFailure;
+
+/// Flattens a secret into flat keys joined with `separator`.
+///
+/// Nested objects produce `parent{separator}child` keys and array items use their
+/// index as the key segment (`hosts{separator}0`). Like environment variables, every
+/// scalar leaf is exposed as a string; `null` leaves become nil values. With the
+/// `arbitrary-precision` feature, numbers keep the exact text Vault returned.
+///
+/// Leaves are tagged with `origin` and their path in the secret, e.g.
+/// `vault://secret/app#db.user` for the flat key `db__user`.
+///
+/// Every flat key that several leaves map to, e.g. `db.user` in `{"db.user": "a",
+/// "db": {"user": "b"}}` with `"."`, is added to `failures`; the first leaf is kept.
+pub(crate) fn flatten(
+    secret: &serde_json::Map<String, JsonValue>,
+    separator: &str,
+    origin: &str,
+    failures: &mut Vec<KeyFailure>,
+) -> Map<String, Value> {
+    let mut flat = Map::new();
+    for (key, value) in secret {
+        let origin = format!("{}#{}", origin, key);
+        flatten_into(&mut flat, key.clone(), origin, value, separator, failures);
+    }
+    flat
+}
+
+/// Flattens `value` into `flat` under `key`; `origin` is where it is in the secret.
+fn flatten_into(
+    flat: &mut Map<String, Value>,
+    key: String,
+    origin: String,
+    value: &JsonValue,
+    separator: &str,
+    failures: &mut Vec<KeyFailure>,
+) {
+    let kind = match value {
+        JsonValue::Object(obj) => {
+            for (child, value) in obj {
+                flatten_into(
+                    flat,
+                    format!("{}{}{}", key, separator, child),
+                    format!("{}.{}", origin, child),
+                    value,
+                    separator,
+                    failures,
+                );
+            }
+            return;
+        }
+        JsonValue::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                flatten_into(
+                    flat,
+                    format!("{}{}{}", key, separator, index),
+                    format!("{}[{}]", origin, index),
+                    value,
+                    separator,
+                    failures,
+                );
+            }
+            return;
+        }
+        JsonValue::Null => ValueKind::Nil,
+        JsonValue::String(s) => ValueKind::String(s.clone()),
+        scalar => ValueKind::String(scalar.to_string()),
+    };
+
+    if flat.contains_key(&key) {
+        let failure = KeyFailure::key(key, "is the flat key of several values");
+        if !failures.contains(&failure) {
+            failures.push(failure);
+        }
+        return;
+    }
+    flat.insert(key, Value::new(Some(&origin), kind));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn flatten_json(secret: JsonValue, separator: &str) -> (Map<String, Value>, Vec<KeyFailure>) {
+        let mut failures = Vec::new();
+        let flat = flatten(
+            secret.as_object().unwrap(),
+            separator,
+            "vault://secret/app",
+            &mut failures,
+        );
+        (flat, failures)
+    }
+
+    #[test]
+    fn flattens_tables_and_arrays() {
+        let (flat, failures) = flatten_json(
+            json!({ "db": { "user": "app", "port": 5432 }, "hosts": ["a", "b"], "tls": null }),
+            "__",
+        );
+        assert!(failures.is_empty());
+
+        let mut keys: Vec<_> = flat.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            ["db__port", "db__user", "hosts__0", "hosts__1", "tls"]
+        );
+        assert_eq!(flat["db__port"].clone().into_string().unwrap(), "5432");
+        assert!(matches!(flat["tls"].kind, ValueKind::Nil));
+        assert_eq!(
+            flat["db__user"].origin(),
+            Some("vault://secret/app#db.user")
+        );
+        assert_eq!(
+            flat["hosts__1"].origin(),
+            Some("vault://secret/app#hosts[1]")
+        );
+    }
+
+    #[test]
+    fn reports_every_colliding_flat_key() {
+        let (flat, failures) = flatten_json(
+            json!({
+                "db.user": "a",
+                "db": { "user": "b", "port": 1 },
+                "hosts.0": "x",
+                "hosts": ["y"],
+            }),
+            ".",
+        );
+        assert_eq!(
+            failures,
+            [
+                KeyFailure::key("db.user", "is the flat key of several values"),
+                KeyFailure::key("hosts.0", "is the flat key of several values"),
+            ]
+        );
+        // The first value in key order is kept.
+        assert_eq!(flat["db.user"].clone().into_string().unwrap(), "b");
+        assert!(flat.contains_key("db.port"));
+    }
+
+    #[test]
+    fn reports_a_collision_once() {
+        let (_, failures) =
+            flatten_json(json!({ "a_b": 1, "a": { "b": 2 }, "a_": { "b": 3 } }), "_");
+        assert_eq!(failures.len(), 1);
+    }
+}
@@ -0,0 +1,16 @@
+//! Randomized delays that keep large fleets from hitting Vault in lockstep.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Returns a random delay between zero and `max`.
+///
+/// Randomness comes from the per-process random seed of `RandomState`, which is
+/// enough to spread restarts without pulling in a random number generator.
+pub(crate) fn random_delay(max: Duration) -> Duration {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(max.as_nanos());
+    let fraction = (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64;
+    max.mul_f64(fraction)
+}
@@ -0,0 +1,304 @@
+//! Renaming of secret keys to the case configuration structs expect.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use config::{ConfigError, Map, Value, ValueKind};
+use serde::Deserialize;
+
+use crate::validation::KeyFailure;
+
+type RenameFn = dyn Fn(&str) -> String + Send + Sync;
+
+/// How the keys of a secret are renamed while loading.
+///
+/// Applies to every key of the secret, including the keys of nested tables and the
+/// flat keys built by flattening. Required keys, computed keys and `{{ key }}`
+/// placeholders use the renamed keys. Two keys that end up with the same name make
+/// `collect` fail.
+///
+/// Parses from `"preserve"`, `"lower"`, `"upper"` and `"kebab"`, so the case can be
+/// chosen in a settings file.
+///
+/// # Example
+///
+/// ```
+/// use config_vault::{KeyCase, VaultSource};
+///
+/// // DB_PASSWORD is loaded as db_password.
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .token("hvs.EXAMPLE_TOKEN")
+///     .mount("secret")
+///     .path("dev")
+///     .key_case(KeyCase::Lower)
+///     .build()
+///     .unwrap();
+///
+/// let strip = KeyCase::custom(|key| key.trim_start_matches("APP_").to_lowercase());
+/// ```
+#[derive(Clone, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum KeyCase {
+    /// Keep keys as they are stored in Vault.
+    #[default]
+    Preserve,
+    /// `DB_PASSWORD` becomes `db_password`.
+    Lower,
+    /// `db_password` becomes `DB_PASSWORD`.
+    Upper,
+    /// `DB_PASSWORD` becomes `db-password`.
+    Kebab,
+    /// Keys are renamed by a user-provided function; see [`KeyCase::custom`].
+    Custom(Arc<RenameFn>),
+}
+
+impl KeyCase {
+    /// Renames keys with `rename`.
+    pub fn custom<F>(rename: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        KeyCase::Custom(Arc::new(rename))
+    }
+
+    fn rename(&self, key: &str) -> String {
+        match self {
+            KeyCase::Preserve => key.to_string(),
+            KeyCase::Lower => key.to_lowercase(),
+            KeyCase::Upper => key.to_uppercase(),
+            KeyCase::Kebab => key.to_lowercase().replace('_', "-"),
+            KeyCase::Custom(rename) => rename(key),
+        }
+    }
+
+    /// Renames the keys of a converted secret and of the tables nested in it.
+    ///
+    /// Every key that several keys are renamed to is added to `failures`, as a path
+    /// with the renamed keys of its tables.
+    pub(crate) fn apply(
+        &self,
+        secret: Map<String, Value>,
+        failures: &mut Vec<KeyFailure>,
+    ) -> Map<String, Value> {
+        if matches!(self, KeyCase::Preserve) {
+            return secret;
+        }
+        self.apply_table(secret, "", failures)
+    }
+
+    fn apply_table(
+        &self,
+        table: Map<String, Value>,
+        parent: &str,
+        failures: &mut Vec<KeyFailure>,
+    ) -> Map<String, Value> {
+        let mut renamed = Map::new();
+        for (key, value) in table {
+            let key = self.rename(&key);
+            let path = if parent.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", parent, key)
+            };
+            let value = self.apply_value(value, &path, failures);
+            if renamed.contains_key(&key) {
+                let failure = KeyFailure::key(path, "is the new name of several keys");
+                if !failures.contains(&failure) {
+                    failures.push(failure);
+                }
+                continue;
+            }
+            renamed.insert(key, value);
+        }
+        renamed
+    }
+
+    fn apply_value(&self, value: Value, path: &str, failures: &mut Vec<KeyFailure>) -> Value {
+        // Keep the origin, which names the key as stored in Vault.
+        let origin = value.origin().map(str::to_string);
+        let kind = match value.kind {
+            ValueKind::Table(table) => ValueKind::Table(self.apply_table(table, path, failures)),
+            ValueKind::Array(items) => ValueKind::Array(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        self.apply_value(item, &format!("{}[{}]", path, index), failures)
+                    })
+                    .collect(),
+            ),
+            kind => kind,
+        };
+        Value::new(origin.as_ref(), kind)
+    }
+}
+
+impl fmt::Debug for KeyCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyCase::Preserve => f.write_str("Preserve"),
+            KeyCase::Lower => f.write_str("Lower"),
+            KeyCase::Upper => f.write_str("Upper"),
+            KeyCase::Kebab => f.write_str("Kebab"),
+            KeyCase::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl PartialEq for KeyCase {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (KeyCase::Custom(a), KeyCase::Custom(b)) => Arc::ptr_eq(a, b),
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+        }
+    }
+}
+
+impl FromStr for KeyCase {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(KeyCase::Preserve),
+            "lower" => Ok(KeyCase::Lower),
+            "upper" => Ok(KeyCase::Upper),
+            "kebab" => Ok(KeyCase::Kebab),
+            other => Err(ConfigError::Message(format!(
+                "Unknown key case '{}'; expected preserve, lower, upper or kebab",
+                other
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for KeyCase {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(&str, Value)]) -> Map<String, Value> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    fn sorted_keys(table: &Map<String, Value>) -> Vec<&str> {
+        let mut keys: Vec<&str> = table.keys().map(String::as_str).collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn renames_keys_to_the_case() {
+        let secret = table(&[
+            ("DB_PASSWORD", Value::from("x")),
+            ("api_key", Value::from("y")),
+        ]);
+        let cases = [
+            (KeyCase::Preserve, ["DB_PASSWORD", "api_key"]),
+            (KeyCase::Lower, ["api_key", "db_password"]),
+            (KeyCase::Upper, ["API_KEY", "DB_PASSWORD"]),
+            (KeyCase::Kebab, ["api-key", "db-password"]),
+        ];
+        for (case, expected) in cases {
+            let mut failures = Vec::new();
+            let renamed = case.apply(secret.clone(), &mut failures);
+            assert!(failures.is_empty(), "{:?}", case);
+            assert_eq!(sorted_keys(&renamed), expected, "{:?}", case);
+        }
+    }
+
+    #[test]
+    fn renames_nested_keys_and_keeps_origins() {
+        let item = Value::new(
+            Some(&"vault://secret/app#Servers[0]".to_string()),
+            ValueKind::Table(table(&[("Host", Value::from("a"))])),
+        );
+        let secret = table(&[("Servers", Value::from(vec![item]))]);
+
+        let mut failures = Vec::new();
+        let renamed = KeyCase::Lower.apply(secret, &mut failures);
+        assert!(failures.is_empty());
+
+        let servers = renamed["servers"].clone().into_array().unwrap();
+        assert_eq!(servers[0].origin(), Some("vault://secret/app#Servers[0]"));
+        let server = servers[0].clone().into_table().unwrap();
+        assert_eq!(sorted_keys(&server), ["host"]);
+    }
+
+    #[test]
+    fn custom_case() {
+        let secret = table(&[("APP_DB_USER", Value::from("x"))]);
+        let case = KeyCase::custom(|key| key.trim_start_matches("APP_").to_lowercase());
+        let renamed = case.apply(secret, &mut Vec::new());
+        assert_eq!(sorted_keys(&renamed), ["db_user"]);
+    }
+
+    #[test]
+    fn reports_collisions_as_key_failures() {
+        let secret = table(&[
+            ("db_pass", Value::from("a")),
+            ("DB_PASS", Value::from("b")),
+            (
+                "nested",
+                Value::from(table(&[("Key", Value::from(1)), ("KEY", Value::from(2))])),
+            ),
+        ]);
+        let mut failures = Vec::new();
+        KeyCase::Upper.apply(secret, &mut failures);
+        failures.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(
+            failures,
+            [
+                KeyFailure::key("DB_PASS", "is the new name of several keys"),
+                KeyFailure::key("NESTED.KEY", "is the new name of several keys"),
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_case_collisions() {
+        // dbPass and db_pass both become DB_PASS.
+        let snake_upper = KeyCase::custom(|key| {
+            let mut renamed = String::new();
+            for c in key.chars() {
+                if c.is_uppercase() {
+                    renamed.push('_');
+                }
+                renamed.push(c.to_ascii_uppercase());
+            }
+            renamed
+        });
+        let secret = table(&[("dbPass", Value::from("a")), ("db_pass", Value::from("b"))]);
+        let mut failures = Vec::new();
+        let renamed = snake_upper.apply(secret, &mut failures);
+        assert_eq!(sorted_keys(&renamed), ["DB_PASS"]);
+        assert_eq!(
+            failures,
+            [KeyFailure::key(
+                "DB_PASS",
+                "is the new name of several keys"
+            )]
+        );
+    }
+
+    #[test]
+    fn parses_case_names() {
+        assert_eq!("kebab".parse::<KeyCase>().unwrap(), KeyCase::Kebab);
+        assert_eq!(
+            KeyCase::try_from("upper".to_string()).unwrap(),
+            KeyCase::Upper
+        );
+        assert!("camel".parse::<KeyCase>().is_err());
+    }
+}
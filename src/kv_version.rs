@@ -0,0 +1,58 @@
+//! The KV version selector that predates [`EngineKind`].
+
+// The derived impls of a deprecated type use it too.
+#![allow(deprecated)]
+
+use std::fmt;
+use std::str::FromStr;
+
+use config::ConfigError;
+use serde::Deserialize;
+
+use crate::EngineKind;
+
+/// Version of the KV secrets engine.
+///
+/// Superseded by [`EngineKind`], whose `Kv1` and `Kv2` variants it maps to. Parses
+/// the same spellings as `EngineKind` (`"v1"`, `"kv1"`, `"v2"`, `"kv2"`), both with
+/// `FromStr` and when deserialized.
+#[deprecated(note = "use EngineKind::Kv1 or EngineKind::Kv2")]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum KvVersion {
+    V1 = 1,
+    #[default]
+    V2,
+}
+
+impl FromStr for KvVersion {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<EngineKind>() {
+            Ok(EngineKind::Kv1) => Ok(KvVersion::V1),
+            Ok(EngineKind::Kv2) => Ok(KvVersion::V2),
+            _ => Err(ConfigError::Message(format!(
+                "Unknown KV version '{}'; expected v1 or v2",
+                s
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for KvVersion {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for KvVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KvVersion::V1 => f.write_str("v1"),
+            KvVersion::V2 => f.write_str("v2"),
+        }
+    }
+}
@@ -15,10 +15,10 @@
 //!
 //! fn load_config() -> Result<Config, ConfigError> {
 //!     let vault_source = VaultSource::new(
-//!         "http://127.0.0.1:8200".to_string(),  // Vault address
-//!         "hvs.EXAMPLE_TOKEN".to_string(),      // Vault token
-//!         "secret".to_string(),                 // KV mount name
-//!         "dev".to_string(),        // Secret path
+//!         "http://127.0.0.1:8200",  // Vault address
+//!         "hvs.EXAMPLE_TOKEN",      // Vault token
+//!         "secret",                 // KV mount name
+//!         "dev",        // Secret path
 //!     );
 //!
 //!     Config::builder()
@@ -34,59 +34,323 @@
 //! use config_vault::VaultSource;
 //!
 //! let vault_source = VaultSource::new_v1(
-//!         "http://127.0.0.1:8200".to_string(),  // Vault address
-//!         "hvs.EXAMPLE_TOKEN".to_string(),      // Vault token
-//!         "secret".to_string(),                 // KV mount name
-//!         "dev".to_string(),        // Secret path
+//!         "http://127.0.0.1:8200",  // Vault address
+//!         "hvs.EXAMPLE_TOKEN",      // Vault token
+//!         "secret",                 // KV mount name
+//!         "dev",        // Secret path
 //! );
 //! ```
+//!
+//! ## Structured secrets
+//!
+//! Secret values keep their JSON type, and nested objects and arrays are loaded as
+//! tables and arrays, so a secret such as
+//! `{"database": {"host": "db.internal", "port": 5432}}` deserializes like any other
+//! structured source:
+//!
+//! ```no_run
+//! use config::Config;
+//! use config_vault::VaultSource;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Database {
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! let config = Config::builder()
+//!     .add_source(VaultSource::new(
+//!         "http://127.0.0.1:8200",
+//!         "hvs.EXAMPLE_TOKEN",
+//!         "secret",
+//!         "dev",
+//!     ))
+//!     .build()?;
+//! let database: Database = config.get("database")?;
+//! # Ok::<(), config::ConfigError>(())
+//! ```
+//!
+//! A secret that holds only the database fields can be loaded below the same key with
+//! [`VaultSource::set_nested_at`].
+//!
+//! ## Feature flags
+//!
+//! * `blocking` (default) - Blocking HTTP client and the `config::Source` implementation
+//!   of [`VaultSource`]. Disable default features to compile out `reqwest::blocking`.
+//! * `rustls` (default) - TLS through rustls with the bundled Mozilla root
+//!   certificates, so no OpenSSL is needed (e.g. in musl or scratch containers).
+//! * `native-tls` - TLS through the platform library (OpenSSL on Linux), which also
+//!   reads PKCS#12 client certificates. Wins when both TLS features are enabled. One of
+//!   the two is required.
+//! * `socks` - SOCKS4 and SOCKS5 proxy URLs in [`VaultSource::set_proxy`], e.g. to reach
+//!   Vault through an `ssh -D` tunnel.
+//! * `async` - `config::AsyncSource` implementation of [`VaultSource`] using the async
+//!   `reqwest::Client`, for services running inside a tokio runtime.
+//! * `aws` - AWS IAM (SigV4-signed) and EC2 auth methods (implies `blocking`).
+//! * `alicloud` - AliCloud auth method (implies `blocking`).
+//! * `arbitrary-precision` - Enables `serde_json/arbitrary_precision`, so numbers in
+//!   a secret become strings exactly as Vault returned them instead of going through
+//!   `f64`. Decimal secrets such as billing rates (`0.10`) keep every digit.
+//! * `age` - [`render::AgeRenderer`], which encrypts rendered configuration to age
+//!   recipients so a snapshot of the secrets can be committed.
+//! * `read-only` - Compiles out every API that writes to, rolls back or revokes state
+//!   in Vault, so strictly read-only consumers can statically guarantee they never
+//!   mutate Vault. Loading configuration through [`VaultSource`] is always available.
+
+// Without a transport nothing fetches secrets, so the processing pipeline is unused.
+#![cfg_attr(not(any(feature = "blocking", feature = "async")), allow(dead_code))]
+
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+compile_error!("config-vault needs a TLS backend: enable the `rustls` or `native-tls` feature");
+
+pub mod access;
+#[cfg(feature = "async")]
+mod async_source;
+#[cfg(feature = "blocking")]
+pub mod auth;
+#[cfg(feature = "blocking")]
+mod blocking;
+mod builder;
+mod convert;
+#[cfg(feature = "blocking")]
+pub mod decrypt;
+pub mod diagnostics;
+pub mod diff;
+pub mod docker;
+mod engine;
+mod env;
+pub mod environments;
+pub mod events;
+mod filter;
+pub mod fingerprint;
+mod flatten;
+mod jitter;
+mod keycase;
+mod kv_version;
+mod plan;
+pub mod precedence;
+mod prefetch;
+mod provenance;
+mod proxy;
+pub mod render;
+mod report;
+#[cfg(all(feature = "blocking", not(feature = "read-only")))]
+mod rollback;
+mod settings;
+mod template;
+mod text;
+mod tls;
+#[cfg(all(feature = "blocking", not(feature = "read-only")))]
+pub mod token;
+pub mod transport;
+pub mod validation;
 
 use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use config::{ConfigError, Map, Source, Value};
-use reqwest::blocking::Client;
+use config::{ConfigError, Map, Value, ValueKind};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
 use serde_json::Value as JsonValue;
 use url::Url;
 
+/// Number of times a request rejected with 412 (index not yet replicated) is retried.
+const CONSISTENCY_RETRIES: u32 = 5;
+
+/// Number of times a request throttled with 429 or 503 and a `Retry-After` hint is retried.
+const THROTTLE_RETRIES: u32 = 3;
+
+/// Timeout of each request to Vault unless set with [`VaultSource::set_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest `Retry-After` hint that is waited for; longer hints fail the request.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Returns how long to wait before retrying a request that got this response, or
+/// `None` if the response is final.
+///
+/// 412 (Vault Enterprise standby not caught up yet) is retried with a short
+/// exponential backoff. 429 (rate limit quota) and 503 are only retried when Vault
+/// sends a `Retry-After` hint, waiting as long as it asks.
+fn retry_delay(status: StatusCode, headers: &HeaderMap, attempt: u32) -> Option<Duration> {
+    match status {
+        StatusCode::PRECONDITION_FAILED if attempt < CONSISTENCY_RETRIES => {
+            Some(Duration::from_millis(20 << (attempt + 1)))
+        }
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+            if attempt < THROTTLE_RETRIES =>
+        {
+            headers
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_secs)
+                .filter(|&delay| delay <= MAX_RETRY_AFTER)
+        }
+        _ => None,
+    }
+}
+
+pub use builder::VaultSourceBuilder;
+pub use engine::EngineKind;
+pub use keycase::KeyCase;
+#[allow(deprecated)]
+pub use kv_version::KvVersion;
+pub use plan::RequestPlan;
+pub use prefetch::CollectedSource;
+#[cfg(feature = "blocking")]
+pub use prefetch::PrefetchHandle;
+pub use provenance::provenance;
+pub use report::{AgentCacheStatus, LoadReport};
+pub use settings::VaultSettings;
+pub use text::TextPolicy;
+
+#[cfg(feature = "blocking")]
+use auth::{AuthMethod, LoginCache};
+#[cfg(feature = "blocking")]
+use decrypt::{Decryptor, PrefixDecryptor};
+use events::{EventKind, EventLog};
+use filter::KeyFilter;
+use proxy::ProxySetting;
+use report::ReportCallback;
+use tls::{CaSource, ClientIdentity};
+use transport::VaultTransport;
+use validation::{KeyFailure, ValidationError};
+
 /// A `Source` for the `config` library that loads configurations from HashiCorp Vault.
 ///
 /// This source connects to a HashiCorp Vault server and loads a secret from
 /// the version 2 of the KV (Key-Value) engine. The values from the secret are included
 /// in the configuration as flat key-value pairs.
 ///
+/// Every loaded value records its origin as `vault://<mount>/<path>#<key>`, which
+/// [`provenance`] reads back from the final `Config`.
+///
 /// # Example
 ///
 /// ```
 /// use config_vault::VaultSource;
 ///
 /// let vault = VaultSource::new(
-///     "http://vault.example.com:8200".to_string(),
-///     "my-token".to_string(),
-///     "secret".to_string(),
-///     "dev".to_string(),
+///     "http://vault.example.com:8200",
+///     "my-token",
+///     "secret",
+///     "dev",
 /// );
 /// ```
 #[derive(Debug, Clone)]
 pub struct VaultSource {
     vault_addr: String,
     vault_token: String,
+    #[cfg(feature = "blocking")]
+    auth: Option<Arc<dyn AuthMethod>>,
+    #[cfg(feature = "blocking")]
+    login_cache: Option<LoginCache>,
+    #[cfg(feature = "blocking")]
+    decryptors: Vec<PrefixDecryptor>,
     vault_mount: String,
     vault_path: String,
-    kv_version: KvVersion,
+    engine: EngineKind,
+    custom_endpoint: Option<CustomEndpoint>,
+    namespace: Option<String>,
+    ca_certificates: Vec<CaSource>,
+    client_identity: Option<ClientIdentity>,
+    accept_invalid_certs: bool,
+    proxy: ProxySetting,
+    transport: Option<Arc<dyn VaultTransport>>,
+    #[cfg(feature = "blocking")]
+    http_client: Option<reqwest::blocking::Client>,
+    #[cfg(feature = "async")]
+    async_http_client: Option<reqwest::Client>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    audit_headers: Vec<(String, String)>,
+    pem_bundles: Vec<PemBundle>,
+    interpolate: bool,
+    computed_keys: Vec<ComputedKey>,
+    required_keys: Vec<String>,
+    key_filter: KeyFilter,
+    flatten_separator: Option<String>,
+    json_key: Option<String>,
+    nested_at: Option<String>,
+    expiry_keys: bool,
+    max_keys: Option<usize>,
+    max_value_size: Option<usize>,
+    text_policy: TextPolicy,
+    key_renames: Vec<(String, String)>,
+    key_case: KeyCase,
+    report_callback: Option<ReportCallback>,
+    event_log: Option<EventLog>,
+    startup_jitter: Option<Duration>,
+    jitter_applied: Arc<AtomicBool>,
+    vault_index: Arc<Mutex<Option<String>>>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum KvVersion {
-    V1 = 1,
-    V2,
+/// A PEM bundle assembled from several fields of the same secret.
+#[derive(Debug, Clone)]
+struct PemBundle {
+    key: String,
+    fields: Vec<String>,
 }
 
-impl KvVersion {
-    fn get_api_path(&self, mount: &str, path: &str) -> String {
-        match self {
-            KvVersion::V1 => format!("v1/{}/{}", mount, path),
-            _ => format!("v1/{}/data/{}", mount, path),
+impl PemBundle {
+    /// Concatenates the PEM blocks of the configured fields, in order.
+    fn assemble(&self, secret: &serde_json::Map<String, JsonValue>) -> Result<String, KeyFailure> {
+        let mut pem = String::new();
+        for field in &self.fields {
+            let block = secret.get(field).and_then(|v| v.as_str()).ok_or_else(|| {
+                KeyFailure::key(
+                    self.key.as_str(),
+                    format!(
+                        "cannot be assembled: PEM field '{}' is missing or not a string",
+                        field
+                    ),
+                )
+            })?;
+            pem.push_str(block.trim_end());
+            pem.push('\n');
         }
+        Ok(pem)
+    }
+}
+
+type ComputeFn = dyn Fn(&Map<String, Value>) -> Result<Value, ConfigError> + Send + Sync;
+
+/// A config key derived from the fetched values by a user-provided closure.
+#[derive(Clone)]
+struct ComputedKey {
+    key: String,
+    compute: Arc<ComputeFn>,
+}
+
+impl fmt::Debug for ComputedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComputedKey")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+type ExtractFn =
+    dyn Fn(&JsonValue) -> Result<serde_json::Map<String, JsonValue>, ConfigError> + Send + Sync;
+
+/// A read path outside the KV engines, with a user-provided response extractor.
+#[derive(Clone)]
+struct CustomEndpoint {
+    path: String,
+    extract: Arc<ExtractFn>,
+}
+
+impl fmt::Debug for CustomEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomEndpoint")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
     }
 }
 
@@ -106,25 +370,25 @@ impl VaultSource {
     /// use config_vault::VaultSource;
     ///
     /// let source = VaultSource::new(
-    ///     "http://127.0.0.1:8200".to_string(),
-    ///     "hvs.EXAMPLE_TOKEN".to_string(),
-    ///     "secret".to_string(),
-    ///     "dev".to_string(),
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     "secret",
+    ///     "dev",
     /// );
     /// ```
     pub fn new(
-        vault_addr: String,
-        vault_token: String,
-        vault_mount: String,
-        vault_path: String,
+        vault_addr: impl Into<String>,
+        vault_token: impl Into<String>,
+        vault_mount: impl Into<String>,
+        vault_path: impl Into<String>,
     ) -> Self {
-        Self {
-            vault_addr,
-            vault_token,
-            vault_mount,
-            vault_path,
-            kv_version: KvVersion::V2,
-        }
+        Self::with_engine(
+            vault_addr.into(),
+            vault_token.into(),
+            vault_mount.into(),
+            vault_path.into(),
+            EngineKind::Kv2,
+        )
     }
 
     /// Creates a new instance of `VaultSource` with kv_version V1
@@ -142,24 +406,102 @@ impl VaultSource {
     /// use config_vault::VaultSource;
     ///
     /// let source = VaultSource::new_v1(
-    ///     "http://127.0.0.1:8200".to_string(),
-    ///     "hvs.EXAMPLE_TOKEN".to_string(),
-    ///     "secret".to_string(),
-    ///     "dev".to_string(),
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     "secret",
+    ///     "dev",
     /// );
     /// ```
     pub fn new_v1(
+        vault_addr: impl Into<String>,
+        vault_token: impl Into<String>,
+        vault_mount: impl Into<String>,
+        vault_path: impl Into<String>,
+    ) -> Self {
+        Self::with_engine(
+            vault_addr.into(),
+            vault_token.into(),
+            vault_mount.into(),
+            vault_path.into(),
+            EngineKind::Kv1,
+        )
+    }
+
+    /// Creates a [`VaultSourceBuilder`] to configure a `VaultSource` step by step.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use config_vault::{EngineKind, VaultSource};
+    ///
+    /// let source = VaultSource::builder()
+    ///     .addr("http://127.0.0.1:8200")
+    ///     .token("hvs.EXAMPLE_TOKEN")
+    ///     .mount("secret")
+    ///     .path("dev")
+    ///     .engine(EngineKind::Kv2)
+    ///     .timeout(Duration::from_secs(5))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> VaultSourceBuilder {
+        VaultSourceBuilder::new()
+    }
+
+    pub(crate) fn with_engine(
         vault_addr: String,
         vault_token: String,
         vault_mount: String,
         vault_path: String,
+        engine: EngineKind,
     ) -> Self {
         Self {
             vault_addr,
             vault_token,
+            #[cfg(feature = "blocking")]
+            auth: None,
+            #[cfg(feature = "blocking")]
+            login_cache: None,
+            #[cfg(feature = "blocking")]
+            decryptors: Vec::new(),
             vault_mount,
             vault_path,
-            kv_version: KvVersion::V1,
+            engine,
+            custom_endpoint: None,
+            namespace: None,
+            ca_certificates: Vec::new(),
+            client_identity: None,
+            accept_invalid_certs: false,
+            proxy: ProxySetting::System,
+            transport: None,
+            #[cfg(feature = "blocking")]
+            http_client: None,
+            #[cfg(feature = "async")]
+            async_http_client: None,
+            timeout: Some(DEFAULT_TIMEOUT),
+            connect_timeout: None,
+            audit_headers: Vec::new(),
+            pem_bundles: Vec::new(),
+            interpolate: false,
+            computed_keys: Vec::new(),
+            required_keys: Vec::new(),
+            key_filter: KeyFilter::default(),
+            flatten_separator: None,
+            json_key: None,
+            nested_at: None,
+            expiry_keys: false,
+            max_keys: None,
+            max_value_size: None,
+            text_policy: TextPolicy::Preserve,
+            key_renames: Vec::new(),
+            key_case: KeyCase::Preserve,
+            report_callback: None,
+            event_log: None,
+            startup_jitter: None,
+            jitter_applied: Arc::new(AtomicBool::new(false)),
+            vault_index: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -167,8 +509,630 @@ impl VaultSource {
     ///
     /// This function takes the target KvVersion and replaces the existing one.
     ///
+    #[deprecated(note = "use set_engine with EngineKind::Kv1 or EngineKind::Kv2")]
+    #[allow(deprecated)]
     pub fn set_kv_version(&mut self, kv_version: KvVersion) {
-        self.kv_version = kv_version;
+        self.engine = kv_version.into();
+    }
+
+    /// Reads the secret from another secrets engine; see [`EngineKind`]. Replaces the
+    /// KV version.
+    pub fn set_engine(&mut self, engine: EngineKind) {
+        self.engine = engine;
+    }
+
+    /// Replaces the static token with an authentication method.
+    ///
+    /// The method logs in lazily, every time `collect` runs, so credentials such as a
+    /// Kubernetes service account token are only read when Vault is queried. See the
+    /// [`auth`] module for the built-in methods.
+    #[cfg(feature = "blocking")]
+    pub fn set_auth(&mut self, auth: Box<dyn AuthMethod>) {
+        self.auth = Some(Arc::from(auth));
+    }
+
+    /// Reuses the token of a login across `collect` calls (and clones of this source)
+    /// instead of logging in every time.
+    ///
+    /// The source logs in again once two thirds of the token's time to live have
+    /// passed, before it expires. This suits auth methods that yield tokens which
+    /// cannot be renewed, such as batch tokens or short-lived Kubernetes logins. A
+    /// token Vault rejects with 403 is dropped, so the next `collect` logs in again.
+    /// Tokens without a TTL are reused until then.
+    #[cfg(feature = "blocking")]
+    pub fn set_reuse_login(&mut self, enabled: bool) {
+        self.login_cache = enabled.then(LoginCache::default);
+    }
+
+    /// Decrypts the string values of the secret that start with `prefix` with
+    /// `decryptor` while loading; see the [`decrypt`] module.
+    ///
+    /// Decryption runs before any other processing, so the plaintext is what gets
+    /// flattened, interpolated and checked. When several prefixes match a value, the
+    /// first registered decryptor wins.
+    #[cfg(feature = "blocking")]
+    pub fn add_decryptor(&mut self, prefix: impl Into<String>, decryptor: Box<dyn Decryptor>) {
+        self.decryptors.push(PrefixDecryptor {
+            prefix: prefix.into(),
+            decryptor: Arc::from(decryptor),
+        });
+    }
+
+    /// Adds a PEM bundle assembled from several fields of the secret.
+    ///
+    /// When a secret stores the certificate, chain and key in separate fields, the
+    /// listed `fields` are concatenated in order and exposed under the new config `key`.
+    /// The original fields are still loaded as usual.
+    ///
+    /// `fields` name top-level fields of the secret as stored in Vault: renames, key
+    /// case and include or exclude patterns do not apply to them, so a field can be
+    /// bundled while being renamed or filtered out of the loaded values. A missing
+    /// field, or one that is not a string, makes `collect` fail.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use config_vault::VaultSource;
+    ///
+    /// let mut source = VaultSource::new(
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     "secret",
+    ///     "tls",
+    /// );
+    /// source.add_pem_bundle(
+    ///     "fullchain",
+    ///     vec!["certificate".to_string(), "ca_chain".to_string()],
+    /// );
+    /// ```
+    pub fn add_pem_bundle(
+        &mut self,
+        key: impl Into<String>,
+        fields: impl IntoIterator<Item = impl Into<String>>,
+    ) {
+        self.pem_bundles.push(PemBundle {
+            key: key.into(),
+            fields: fields.into_iter().map(Into::into).collect(),
+        });
+    }
+
+    /// Enables `{{ key }}` interpolation between values of the secret.
+    ///
+    /// When enabled, placeholders in string values are replaced with the value of the
+    /// referenced key of the same secret during `collect`, so a value such as
+    /// `postgres://{{user}}:{{password}}@{{host}}/app` can be composed inside Vault.
+    /// Strings nested in tables and arrays are interpolated too; nested values are
+    /// referenced by their dotted path, such as `{{ db.host }}`. Unknown keys and
+    /// cyclic references make `collect` fail.
+    ///
+    /// Placeholders only reference keys of this secret: values of other sources
+    /// layered into the same `Config` are merged after `collect` and cannot be
+    /// referenced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use config::{Config, ConfigError};
+    /// use config_vault::transport::{TransportRequest, TransportResponse, VaultTransport};
+    /// use config_vault::VaultSource;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Debug)]
+    /// struct FixedSecret;
+    ///
+    /// impl VaultTransport for FixedSecret {
+    ///     fn send(&self, _: &TransportRequest) -> Result<TransportResponse, ConfigError> {
+    ///         let data = json!({
+    ///             "user": "app",
+    ///             "db": { "host": "db.internal", "url": "postgres://{{ user }}@{{ db.host }}/app" }
+    ///         });
+    ///         Ok(TransportResponse::json(200, &json!({ "data": { "data": data } })))
+    ///     }
+    /// }
+    ///
+    /// let source = VaultSource::builder()
+    ///     .addr("http://vault.test:8200")
+    ///     .token("hvs.EXAMPLE_TOKEN")
+    ///     .mount("secret")
+    ///     .path("dev")
+    ///     .interpolation(true)
+    ///     .transport(Box::new(FixedSecret))
+    ///     .build()?;
+    ///
+    /// # #[cfg(feature = "blocking")]
+    /// # {
+    /// let config = Config::builder().add_source(source).build()?;
+    /// assert_eq!(config.get_string("db.url")?, "postgres://app@db.internal/app");
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn set_interpolation(&mut self, enabled: bool) {
+        self.interpolate = enabled;
+    }
+
+    /// Flattens nested secret data into environment-style flat keys.
+    ///
+    /// With a separator such as `"__"`, the secret `{"db": {"user": "app"}}` is loaded
+    /// as the flat key `db__user`. Array items use their index as the key segment and
+    /// every scalar leaf is exposed as a string, the way environment variables are.
+    /// With `"."`, `{"db": {"pool": {"max": 5}}}` becomes `db.pool.max`. Two values
+    /// that map to the same flat key make `collect` fail.
+    ///
+    /// Flattening replaces the default nested layout, and cannot be combined with
+    /// [`set_json_key`](Self::set_json_key). Pass `None` to disable flattening: values
+    /// then keep their JSON type (booleans, integers, floats, nil, tables and arrays).
+    pub fn set_flatten_separator(&mut self, separator: Option<String>) {
+        self.flatten_separator = separator;
+    }
+
+    /// Loads the whole secret data object as a single key holding serialized JSON.
+    ///
+    /// For applications that pass the secret through to another system untouched:
+    /// with the key `"credentials"`, the secret `{"user": "app", "port": 5432}` is
+    /// loaded as `credentials = '{"port":5432,"user":"app"}'`. Interpolation does not
+    /// apply and the builder rejects a flatten separator along with it; PEM bundles
+    /// and computed keys are still added next to it. Pass `None` to load the fields as
+    /// separate keys again.
+    pub fn set_json_key(&mut self, key: Option<String>) {
+        self.json_key = key;
+    }
+
+    /// Loads every key of the secret under the parent key `parent` instead of the
+    /// configuration root.
+    ///
+    /// With the parent `"database"`, the secret `{"user": "app"}` is read back as
+    /// `database.user`, so it can be merged with file sources that already use
+    /// namespaced keys. A dotted parent such as `"services.billing"` nests several
+    /// levels. Required keys and computed keys still name keys of the secret itself.
+    /// Pass `None` to load the keys at the root again.
+    pub fn set_nested_at(&mut self, parent: Option<String>) {
+        self.nested_at = parent;
+    }
+
+    /// Adds a `<key>__expires_at` companion key next to every loaded key when Vault
+    /// returns the secret with a lease, as dynamic credentials read through
+    /// [`set_custom_endpoint`](Self::set_custom_endpoint) do.
+    ///
+    /// The companion holds the end of the lease as Unix seconds (load time plus
+    /// `lease_duration`), so application code can check whether the credentials
+    /// are still valid from the configuration alone. Secrets without a lease get no
+    /// companion keys.
+    pub fn set_expiry_keys(&mut self, enabled: bool) {
+        self.expiry_keys = enabled;
+    }
+
+    /// Registers a key whose value is computed from the fetched values.
+    ///
+    /// The closure receives the values collected so far and is evaluated during
+    /// `collect`, after interpolation and PEM bundles, so the final `Config` already
+    /// contains the derived value. Computed keys are evaluated in registration order
+    /// and can see the keys computed before them. They are only evaluated when every
+    /// other check of the secret passed; an error of the closure is reported as a
+    /// validation failure of the key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use config::{ConfigError, Value};
+    /// use config_vault::VaultSource;
+    ///
+    /// let mut source = VaultSource::new(
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     "secret",
+    ///     "database",
+    /// );
+    /// source.add_computed_key("dsn", |values| {
+    ///     let get = |key: &str| {
+    ///         values
+    ///             .get(key)
+    ///             .cloned()
+    ///             .ok_or_else(|| ConfigError::NotFound(key.to_string()))?
+    ///             .into_string()
+    ///     };
+    ///     Ok(Value::from(format!(
+    ///         "postgres://{}:{}@{}/app",
+    ///         get("user")?,
+    ///         get("password")?,
+    ///         get("host")?
+    ///     )))
+    /// });
+    /// ```
+    pub fn add_computed_key<F>(&mut self, key: impl Into<String>, compute: F)
+    where
+        F: Fn(&Map<String, Value>) -> Result<Value, ConfigError> + Send + Sync + 'static,
+    {
+        self.computed_keys.push(ComputedKey {
+            key: key.into(),
+            compute: Arc::new(compute),
+        });
+    }
+
+    /// Makes `collect` fail when `key` is missing from the loaded values.
+    ///
+    /// Keys are checked after flattening and computed keys, so a secret that lacks a
+    /// setting the application cannot start without is reported at load time, with
+    /// every missing key named, instead of on first use. Keys nested in tables are
+    /// given as dotted paths, such as `database.host`.
+    pub fn add_required_key(&mut self, key: impl Into<String>) {
+        self.required_keys.push(key.into());
+    }
+
+    /// Loads only the keys of the secret that match `pattern`, or one of the other
+    /// include patterns.
+    ///
+    /// Patterns are globs where `*` matches any run of characters and `?` a single
+    /// character, e.g. `db_*`. They select top-level keys, after flattening and
+    /// [`set_key_case`](Self::set_key_case), so keys written into the same path by
+    /// another team are not imported. Filtered keys are not counted against
+    /// [`set_max_keys`](Self::set_max_keys) and cannot be referenced by `{{ key }}`
+    /// placeholders. Filters do not apply with [`set_json_key`](Self::set_json_key).
+    pub fn add_include_key(&mut self, pattern: impl Into<String>) {
+        self.key_filter.include.push(pattern.into());
+    }
+
+    /// Skips the keys of the secret that match `pattern`, even when an include
+    /// pattern matches them; see [`add_include_key`](Self::add_include_key).
+    pub fn add_exclude_key(&mut self, pattern: impl Into<String>) {
+        self.key_filter.exclude.push(pattern.into());
+    }
+
+    /// Makes `collect` fail when the secret has more than `max_keys` keys.
+    ///
+    /// Together with [`set_max_value_size`](Self::set_max_value_size), this guards
+    /// against accidentally importing huge secrets into the memory of every process.
+    /// Keys are counted after flattening, before computed keys are added.
+    pub fn set_max_keys(&mut self, max_keys: usize) {
+        self.max_keys = Some(max_keys);
+    }
+
+    /// Makes `collect` fail when a value of the secret is longer than `max_bytes`.
+    pub fn set_max_value_size(&mut self, max_bytes: usize) {
+        self.max_value_size = Some(max_bytes);
+    }
+
+    /// Sets how values with control characters or invalid UTF-8 are handled; see
+    /// [`TextPolicy`]. Defaults to [`TextPolicy::Preserve`].
+    pub fn set_text_policy(&mut self, policy: TextPolicy) {
+        self.text_policy = policy;
+    }
+
+    /// Sets how the keys of the secret are renamed; see [`KeyCase`]. Defaults to
+    /// [`KeyCase::Preserve`]. The key of [`set_json_key`](Self::set_json_key) is
+    /// never renamed.
+    pub fn set_key_case(&mut self, case: KeyCase) {
+        self.key_case = case;
+    }
+
+    /// Loads the key `from` of the secret as `to`.
+    ///
+    /// Bridges legacy Vault key names to the layout the application expects without
+    /// migrating the secret: `DB_PASS` renamed to `database.password` is read back as
+    /// the nested key `database.password`. Renames apply to the keys as stored in
+    /// Vault (after flattening), before [`set_key_case`](Self::set_key_case), and a
+    /// missing `from` key is skipped. A target that is already a key of the secret
+    /// makes `collect` fail.
+    pub fn add_key_rename(&mut self, from: impl Into<String>, to: impl Into<String>) {
+        self.key_renames.push((from.into(), to.into()));
+    }
+
+    /// Sets the Vault Enterprise (or HCP Vault) namespace, e.g. "team-a/prod".
+    ///
+    /// The namespace is sent as `X-Vault-Namespace` with every request, including
+    /// logins, so mounts, paths and auth methods are all relative to it. Nested
+    /// namespaces are written with `/`. `None` or an empty namespace means the root
+    /// namespace.
+    pub fn set_namespace(&mut self, namespace: Option<String>) {
+        self.namespace = namespace
+            .map(|ns| ns.trim_matches('/').to_string())
+            .filter(|ns| !ns.is_empty());
+    }
+
+    /// Trusts the CA certificates of a PEM bundle to verify Vault's TLS certificate.
+    ///
+    /// Once a CA is added, only the added CAs are trusted, not the system trust store,
+    /// like `VAULT_CACERT` for the Vault CLI. Use this for a Vault served with a
+    /// certificate from an internal CA.
+    pub fn add_ca_certificate(&mut self, pem: Vec<u8>) {
+        self.ca_certificates.push(CaSource::Pem(pem));
+    }
+
+    /// Trusts the CA certificates of a PEM bundle file; see
+    /// [`add_ca_certificate`](Self::add_ca_certificate).
+    ///
+    /// The file is read again every time a client is created, so a rotated bundle is
+    /// picked up without restarting.
+    pub fn add_ca_certificate_file(&mut self, path: impl Into<PathBuf>) {
+        self.ca_certificates.push(CaSource::File(path.into()));
+    }
+
+    /// Trusts the CA certificates of every PEM file in a directory, like `VAULT_CAPATH`;
+    /// see [`add_ca_certificate`](Self::add_ca_certificate).
+    pub fn add_ca_directory(&mut self, path: impl Into<PathBuf>) {
+        self.ca_certificates.push(CaSource::Dir(path.into()));
+    }
+
+    /// Presents a client certificate in the TLS handshake of every request to Vault.
+    ///
+    /// For Vault listeners that require mutual TLS, independently of the auth method.
+    /// `cert` is a PEM certificate (chain) and `key` its PEM private key: PKCS#8, or
+    /// with the `rustls` backend also PKCS#1 or SEC1. An
+    /// auth method with its own certificate, such as
+    /// [`CertAuth`](crate::auth::CertAuth), takes precedence.
+    pub fn set_client_certificate(&mut self, cert: Vec<u8>, key: Vec<u8>) {
+        self.client_identity = Some(ClientIdentity::Pem { cert, key });
+    }
+
+    /// Like [`set_client_certificate`](Self::set_client_certificate), reading the PEM
+    /// files every time a client is created so rotated certificates are picked up.
+    pub fn set_client_certificate_files(
+        &mut self,
+        cert: impl Into<PathBuf>,
+        key: impl Into<PathBuf>,
+    ) {
+        self.client_identity = Some(ClientIdentity::Files {
+            cert: cert.into(),
+            key: key.into(),
+        });
+    }
+
+    /// Like [`set_client_certificate`](Self::set_client_certificate), from a
+    /// DER-encoded PKCS#12 archive. Requires the `native-tls` feature.
+    #[cfg(feature = "native-tls")]
+    pub fn set_client_pkcs12(&mut self, der: Vec<u8>, password: impl Into<String>) {
+        self.client_identity = Some(ClientIdentity::Pkcs12 {
+            der,
+            password: password.into(),
+        });
+    }
+
+    /// Disables verification of Vault's TLS certificate, like `VAULT_SKIP_VERIFY`.
+    ///
+    /// # Warning
+    ///
+    /// Any server can then impersonate Vault and read the token. Only meant for
+    /// development or staging Vaults with self-signed certificates; trusting their CA
+    /// with [`add_ca_certificate`](Self::add_ca_certificate) is preferable.
+    pub fn set_danger_accept_invalid_certs(&mut self, accept: bool) {
+        self.accept_invalid_certs = accept;
+    }
+
+    /// Sends every request to Vault through the proxy at `url` (e.g.
+    /// "http://proxy.example.com:3128"), ignoring the proxy environment variables.
+    ///
+    /// With the `socks` feature, `url` can also be a SOCKS proxy such as the tunnel
+    /// opened by `ssh -D 1080 bastion`: `socks5h://127.0.0.1:1080` lets the proxy
+    /// resolve Vault's host name, which is usually what a bastion needs, while
+    /// `socks5://` resolves it locally. `socks4://` and `socks4a://` are accepted too,
+    /// without credentials.
+    ///
+    /// By default the proxy is taken from `HTTPS_PROXY`, `HTTP_PROXY` or `ALL_PROXY`,
+    /// honoring `NO_PROXY`.
+    pub fn set_proxy(&mut self, url: impl Into<String>) {
+        self.proxy = ProxySetting::Url {
+            url: url.into(),
+            credentials: None,
+        };
+    }
+
+    /// Authenticates to the proxy set with [`set_proxy`](Self::set_proxy) with HTTP
+    /// basic auth, or with username and password for a SOCKS5 proxy.
+    pub fn set_proxy_basic_auth(
+        &mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) {
+        if let ProxySetting::Url { credentials, .. } = &mut self.proxy {
+            *credentials = Some((username.into(), password.into()));
+        }
+    }
+
+    /// Connects to Vault directly, ignoring the proxy environment variables.
+    pub fn set_no_proxy(&mut self) {
+        self.proxy = ProxySetting::Direct;
+    }
+
+    /// Sends requests with `client` instead of a client built for each `collect`, so
+    /// several sources can share its connection pool.
+    ///
+    /// The client is used as is: the timeouts, TLS and proxy settings of this source
+    /// are ignored, and an auth method that presents a client certificate (such as
+    /// [`CertAuth`](crate::auth::CertAuth)) needs that certificate configured on
+    /// `client`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use config_vault::VaultSource;
+    ///
+    /// let client = reqwest::blocking::Client::new();
+    /// let mut db = VaultSource::new("http://127.0.0.1:8200", "hvs.EXAMPLE_TOKEN", "secret", "db");
+    /// let mut api = VaultSource::new("http://127.0.0.1:8200", "hvs.EXAMPLE_TOKEN", "secret", "api");
+    /// db.set_http_client(client.clone());
+    /// api.set_http_client(client);
+    /// ```
+    #[cfg(feature = "blocking")]
+    pub fn set_http_client(&mut self, client: reqwest::blocking::Client) {
+        self.http_client = Some(client);
+    }
+
+    /// Like [`set_http_client`](Self::set_http_client), for the `AsyncSource`
+    /// implementation. Logins through an auth method still use a blocking client.
+    #[cfg(feature = "async")]
+    pub fn set_async_http_client(&mut self, client: reqwest::Client) {
+        self.async_http_client = Some(client);
+    }
+
+    /// Intercepts the requests to Vault with `transport`, which answers them in place
+    /// of the reqwest client; see the [`transport`] module. The requests are still
+    /// built with reqwest, but the HTTP client settings of the source (timeouts, TLS,
+    /// proxy, injected clients) no longer apply.
+    pub fn set_transport(&mut self, transport: Box<dyn VaultTransport>) {
+        self.transport = Some(Arc::from(transport));
+    }
+
+    /// Registers a callback that receives a [`LoadReport`] after each successful load.
+    ///
+    /// The report lists the path, number of keys, secret version and fetch duration,
+    /// but never any values, so it is safe to log at startup when debugging which
+    /// layer a configuration value came from.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use config_vault::VaultSource;
+    ///
+    /// let mut source = VaultSource::new(
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     "secret",
+    ///     "dev",
+    /// );
+    /// source.set_report_callback(|report| eprintln!("vault: {}", report));
+    /// ```
+    pub fn set_report_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&LoadReport) + Send + Sync + 'static,
+    {
+        self.report_callback = Some(ReportCallback(Arc::new(callback)));
+    }
+
+    /// Sends an extra header with every request to Vault, including logins.
+    ///
+    /// Meant for identity headers such as the service or pod name, so audit logs can
+    /// attribute reads to workloads that share a token. Vault only records headers
+    /// listed in its audited request headers (`sys/config/auditing/request-headers`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use config_vault::VaultSource;
+    ///
+    /// let mut source = VaultSource::new(
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     "secret",
+    ///     "dev",
+    /// );
+    /// source.add_audit_header("X-Service-Name", "billing");
+    /// if let Ok(pod) = std::env::var("POD_NAME") {
+    ///     source.add_audit_header("X-Pod-Name", pod);
+    /// }
+    /// ```
+    pub fn add_audit_header(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.audit_headers.push((name.into(), value.into()));
+    }
+
+    /// Records logins, fetches and failures of this source in `log`.
+    ///
+    /// See [`EventLog`] for an example.
+    pub fn set_event_log(&mut self, log: EventLog) {
+        self.event_log = Some(log);
+    }
+
+    /// Sets the total timeout of each request made to Vault, from connecting to
+    /// reading the whole response, or disables it with `None`.
+    ///
+    /// Defaults to [`DEFAULT_TIMEOUT`] (10 seconds), so `collect` fails instead of
+    /// stalling startup when Vault is unreachable. Logins through an auth method and
+    /// each retry are bounded separately.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Sets a shorter timeout for establishing the connection (TCP and TLS
+    /// handshake), to fail fast on an unreachable address while still allowing slow
+    /// responses. Without it, connecting is only bounded by the request timeout.
+    pub fn set_connect_timeout(&mut self, timeout: Option<Duration>) {
+        self.connect_timeout = timeout;
+    }
+
+    /// Delays the first fetch by a random duration of up to `max_delay`.
+    ///
+    /// When a whole fleet restarts at once (e.g. after node reboots), spreading the
+    /// initial reads keeps thousands of instances from hitting Vault in lockstep.
+    /// Only the first `collect` of this source and its clones is delayed; later
+    /// reloads are not.
+    pub fn set_startup_jitter(&mut self, max_delay: Duration) {
+        self.startup_jitter = Some(max_delay);
+    }
+
+    /// Reads from an arbitrary Vault API path instead of a KV secret.
+    ///
+    /// `extract` receives the JSON response of a `GET` on `path` and returns the
+    /// object whose fields become configuration values; the rest of the pipeline
+    /// (flattening, interpolation, computed keys, ...) applies as for KV secrets.
+    /// This lets third-party secret engine plugins be used without support in this
+    /// crate. The mount, path and KV version of the source are ignored for reads, and
+    /// origins and reports name the endpoint path instead.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - API path below the Vault address (e.g. "v1/my-plugin/creds/app")
+    /// * `extract` - Closure selecting the values from the response
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use config::ConfigError;
+    /// use config_vault::VaultSource;
+    ///
+    /// let mut source = VaultSource::new(
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     String::new(),
+    ///     String::new(),
+    /// );
+    /// source.set_custom_endpoint("v1/my-plugin/creds/app", |response| {
+    ///     response
+    ///         .pointer("/data/credentials")
+    ///         .and_then(|v| v.as_object())
+    ///         .cloned()
+    ///         .ok_or_else(|| ConfigError::Message("Plugin response has no credentials".into()))
+    /// });
+    /// ```
+    pub fn set_custom_endpoint<F>(&mut self, path: impl Into<String>, extract: F)
+    where
+        F: Fn(&JsonValue) -> Result<serde_json::Map<String, JsonValue>, ConfigError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.custom_endpoint = Some(CustomEndpoint {
+            path: path.into(),
+            extract: Arc::new(extract),
+        });
+    }
+
+    /// Builds the URL the secret is read from: the custom endpoint if one is set,
+    /// otherwise the KV read API.
+    fn build_read_url(&self) -> Result<Url, ConfigError> {
+        match &self.custom_endpoint {
+            Some(endpoint) => self.build_api_url(&endpoint.path),
+            None => self.build_kv_read_url(),
+        }
+    }
+
+    /// Name of the secret in origins, reports and events: "<mount>/<path>", or the
+    /// custom endpoint path without the `v1/` prefix.
+    pub(crate) fn secret_path(&self) -> String {
+        match &self.custom_endpoint {
+            Some(endpoint) => endpoint
+                .path
+                .trim_start_matches('/')
+                .trim_start_matches("v1/")
+                .to_string(),
+            None => match self.engine {
+                EngineKind::Kv1 | EngineKind::Kv2 => {
+                    format!("{}/{}", self.vault_mount, self.vault_path)
+                }
+                _ => self
+                    .engine
+                    .api_path(&self.vault_mount, &self.vault_path)
+                    .trim_start_matches("v1/")
+                    .to_string(),
+            },
+        }
     }
 
     /// Builds the URL for Vault's KV1/KV2 engine read API.
@@ -180,74 +1144,381 @@ impl VaultSource {
     ///
     /// * `Result<Url, ConfigError>` - The constructed URL or an error if the address is invalid
     fn build_kv_read_url(&self) -> Result<Url, ConfigError> {
-        let api_path = self
-            .kv_version
-            .get_api_path(&self.vault_mount, &self.vault_path);
+        let api_path = self.engine.api_path(&self.vault_mount, &self.vault_path);
 
+        self.build_api_url(&api_path)
+    }
+
+    /// Builds the complete URL of a Vault API path (e.g. "v1/secret/metadata/dev").
+    fn build_api_url(&self, api_path: &str) -> Result<Url, ConfigError> {
         let mut url = Url::parse(&self.vault_addr)
             .map_err(|e| ConfigError::Message(format!("Invalid Vault address URL: {}", e)))?;
 
+        let base = url.clone();
         url.path_segments_mut()
-            .map_err(|_| ConfigError::Message("Vault address URL cannot be a base".into()))?
+            .map_err(|_| diagnostics::not_a_base(&base))?
             .pop_if_empty() // Remove trailing slash if any
             .extend(api_path.split('/')); // Add the API path segments
 
         Ok(url)
     }
-}
 
-impl Source for VaultSource {
-    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
-        Box::new(self.clone())
-    }
-
-    /// Implementation of the `collect` method from `Source`.
-    ///
-    /// This method makes an HTTP request to the Vault API to obtain
-    /// configuration values stored in the specified secret.
+    /// Turns a successful read response into configuration values.
     ///
-    /// # Returns
-    ///
-    /// * `Result<Map<String, Value>, ConfigError>` - A map with configuration values
-    ///   or an error if the request fails or the response format is not as expected.
-    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
-        let url = self.build_kv_read_url()?;
-
-        let client = Client::new();
-        let response = client
-            .get(url)
-            .header("X-Vault-Token", &self.vault_token)
-            .send()
-            .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
-
-        if response.status().is_success() {
-            let raw = response
-                .json::<JsonValue>()
-                .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
-
-            let json_obj = raw
+    /// This is the transport-independent part of `collect`: it extracts the secret
+    /// data, applies flattening, interpolation, PEM bundles and computed keys, tags
+    /// every value with its origin and emits the load report and event.
+    fn process_secret(
+        &self,
+        raw: &JsonValue,
+        started: Instant,
+        agent_cache: Option<AgentCacheStatus>,
+    ) -> Result<Map<String, Value>, ConfigError> {
+        let extracted;
+        let json_obj = match &self.custom_endpoint {
+            Some(endpoint) => {
+                extracted = (endpoint.extract)(raw)?;
+                &extracted
+            }
+            None => raw
                 .get("data")
                 .and_then(|x| {
-                    if self.kv_version == KvVersion::V2 {
+                    if self.engine == EngineKind::Kv2 {
                         x.get("data")
                     } else {
                         Some(x)
                     }
                 })
                 .and_then(|x| x.as_object())
-                .unwrap();
+                .ok_or_else(|| {
+                    ConfigError::Message(format!(
+                        "Vault response for {} has no secret data object (wrong engine or kv version?)",
+                        self.secret_path()
+                    ))
+                })?,
+        };
+
+        // Every problem with the content of the secret is collected here and reported
+        // in one ValidationError.
+        let mut failures = Vec::new();
+
+        // Values are tagged with the key they have in Vault before renames and key
+        // case change it.
+        let origin = format!("vault://{}", self.secret_path());
+        let mut secret = match (&self.json_key, &self.flatten_separator) {
+            (Some(key), _) => {
+                let blob = JsonValue::Object(json_obj.clone()).to_string();
+                HashMap::from([(key.clone(), Value::new(Some(&origin), blob))])
+            }
+            (None, Some(separator)) => {
+                flatten::flatten(json_obj, separator, &origin, &mut failures)
+            }
+            (None, None) => convert::to_table(json_obj)
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = provenance::with_origin(value, &format!("{}#{}", origin, key));
+                    (key, value)
+                })
+                .collect(),
+        };
+
+        if self.json_key.is_none() {
+            self.rename_keys(&mut secret, &mut failures);
+            secret = self.key_case.apply(secret, &mut failures);
+            self.key_filter.retain(&mut secret);
+        }
+
+        self.text_policy.apply(&mut secret, &mut failures);
+        self.check_limits(&secret, &mut failures);
+
+        if self.interpolate && self.json_key.is_none() {
+            template::interpolate(&mut secret, &mut failures);
+        }
+
+        for bundle in &self.pem_bundles {
+            match bundle.assemble(json_obj) {
+                Ok(pem) => {
+                    secret.insert(bundle.key.clone(), Value::from(pem));
+                }
+                Err(failure) => failures.push(failure),
+            }
+        }
+
+        // Computed keys are user code that expects valid values, so they only run on
+        // a secret that passed every other check.
+        let valid = failures.is_empty();
+        if valid {
+            for computed in &self.computed_keys {
+                match (computed.compute)(&secret) {
+                    Ok(value) => {
+                        secret.insert(computed.key.clone(), value);
+                    }
+                    Err(e) => failures.push(KeyFailure::key(
+                        computed.key.as_str(),
+                        format!("cannot be computed: {}", e),
+                    )),
+                }
+            }
+        }
+
+        if let Some(expires_at) = self.lease_expiry(raw) {
+            let keys: Vec<String> = secret.keys().cloned().collect();
+            for key in keys {
+                secret.insert(format!("{}__expires_at", key), Value::from(expires_at));
+            }
+        }
+
+        let missing = self.missing_required_keys(&secret, !valid, &failures);
+        failures.extend(missing);
+        if !failures.is_empty() {
+            return Err(self.validation_error(failures));
+        }
+
+        // Keys added while loading (PEM bundles, computed keys...) have no Vault key
+        // of their own.
+        let secret: Map<String, Value> = secret
+            .into_iter()
+            .map(|(key, value)| {
+                let value = provenance::with_default_origin(value, &format!("{}#{}", origin, key));
+                (key, value)
+            })
+            .collect();
+
+        let version = match self.engine {
+            EngineKind::Kv2 if self.custom_endpoint.is_none() => raw
+                .pointer("/data/metadata/version")
+                .and_then(|v| v.as_u64()),
+            _ => None,
+        };
+
+        self.record_event(EventKind::Fetched {
+            keys: secret.len(),
+            version,
+        });
+
+        if let Some(callback) = &self.report_callback {
+            (callback.0)(&LoadReport {
+                addr: self.vault_addr.clone(),
+                path: self.secret_path(),
+                keys: secret.len(),
+                version,
+                duration: started.elapsed(),
+                agent_cache,
+            });
+        }
+
+        Ok(match &self.nested_at {
+            Some(parent) => secret
+                .into_iter()
+                .map(|(key, value)| (format!("{}.{}", parent, key), value))
+                .collect(),
+            None => secret,
+        })
+    }
 
-            let mut secret = HashMap::new();
-            for (k, v) in json_obj {
-                secret.insert(k.clone(), Value::from(v.as_str().unwrap()));
+    /// Applies the key renames, all at once so that keys can be swapped. Every key
+    /// that several keys are renamed to is added to `failures`.
+    fn rename_keys(&self, secret: &mut Map<String, Value>, failures: &mut Vec<KeyFailure>) {
+        let renamed: Vec<(&String, Value)> = self
+            .key_renames
+            .iter()
+            .filter_map(|(from, to)| Some((to, secret.remove(from)?)))
+            .collect();
+        for (to, value) in renamed {
+            if secret.contains_key(to) {
+                let failure = KeyFailure::key(to.as_str(), "is the new name of several keys");
+                if !failures.contains(&failure) {
+                    failures.push(failure);
+                }
+                continue;
             }
+            secret.insert(to.clone(), value);
+        }
+    }
+
+    /// Checks the key count and value size limits on freshly converted values.
+    fn check_limits(&self, secret: &Map<String, Value>, failures: &mut Vec<KeyFailure>) {
+        if let Some(max_keys) = self.max_keys {
+            if secret.len() > max_keys {
+                failures.push(KeyFailure::secret(format!(
+                    "has {} keys, more than the limit of {}",
+                    secret.len(),
+                    max_keys
+                )));
+            }
+        }
+
+        if let Some(max_bytes) = self.max_value_size {
+            // Strings nested in tables and arrays count too.
+            let mut pending: Vec<(String, &Value)> =
+                secret.iter().map(|(k, v)| (k.clone(), v)).collect();
+            while let Some((key, value)) = pending.pop() {
+                match &value.kind {
+                    ValueKind::String(s) if s.len() > max_bytes => {
+                        failures.push(KeyFailure::key(
+                            key,
+                            format!("is {} bytes, more than the limit of {}", s.len(), max_bytes),
+                        ));
+                    }
+                    ValueKind::Table(table) => {
+                        pending.extend(table.iter().map(|(k, v)| (format!("{}.{}", key, k), v)))
+                    }
+                    ValueKind::Array(items) => pending.extend(
+                        items
+                            .iter()
+                            .enumerate()
+                            .map(|(i, v)| (format!("{}[{}]", key, i), v)),
+                    ),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Reports the required keys missing from the loaded values. Keys that already
+    /// have a failure, and computed keys when `skipped_computed`, are not reported.
+    fn missing_required_keys(
+        &self,
+        secret: &Map<String, Value>,
+        skipped_computed: bool,
+        failures: &[KeyFailure],
+    ) -> Vec<KeyFailure> {
+        let accounted_for = |key: &str| {
+            (skipped_computed && self.computed_keys.iter().any(|c| c.key == key))
+                || failures.iter().any(|f| f.key.as_deref() == Some(key))
+        };
+        self.required_keys
+            .iter()
+            .filter(|key| !secret.contains_key(*key) && precedence::lookup(secret, key).is_none())
+            .filter(|key| !accounted_for(key))
+            .map(|key| KeyFailure::key(key.as_str(), "is missing (required key)"))
+            .collect()
+    }
+
+    fn validation_error(&self, failures: Vec<KeyFailure>) -> ConfigError {
+        ConfigError::Foreign(Box::new(ValidationError::new(self.secret_path(), failures)))
+    }
 
-            Ok(secret)
+    /// End of the lease of a read response as Unix seconds, if expiry keys are
+    /// enabled and the response has a lease.
+    fn lease_expiry(&self, raw: &JsonValue) -> Option<u64> {
+        if !self.expiry_keys {
+            return None;
+        }
+        let lease = raw
+            .get("lease_duration")
+            .and_then(|v| v.as_u64())
+            .filter(|&secs| secs > 0)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+        Some(now.as_secs().saturating_add(lease))
+    }
+
+    /// Returns the startup jitter to wait for, if this is the first fetch.
+    fn take_startup_delay(&self) -> Option<Duration> {
+        let max_delay = self.startup_jitter?;
+        if self.jitter_applied.swap(true, Ordering::SeqCst) {
+            None
         } else {
-            Err(ConfigError::Message(format!(
-                "Failed to fetch secret from Vault (wrong kv version?): {}",
-                response.status()
-            )))
+            Some(jitter::random_delay(max_delay))
+        }
+    }
+
+    /// Builds the error for a failed read answered with `status` and `body`.
+    ///
+    /// A reused login is dropped after Vault rejected its token, so the next `collect`
+    /// logs in again.
+    fn read_failure(&self, status: StatusCode, body: &[u8]) -> ConfigError {
+        #[cfg(feature = "blocking")]
+        if status == StatusCode::FORBIDDEN {
+            if let Some(cache) = &self.login_cache {
+                cache.clear();
+            }
+        }
+
+        if status == StatusCode::NOT_FOUND {
+            let mount = if self.custom_endpoint.is_none() && self.engine.uses_mount() {
+                self.vault_mount.clone()
+            } else {
+                let path = self.secret_path();
+                path.split('/').next().unwrap_or_default().to_string()
+            };
+            if let Some(error) = diagnostics::missing_mount(body, &mount, self.namespace.as_deref())
+            {
+                return error;
+            }
+        }
+
+        ConfigError::Message(format!(
+            "Failed to fetch secret from Vault (wrong kv version?): {}",
+            status
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn retry_after(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn retry_delay_backs_off_on_412() {
+        let headers = HeaderMap::new();
+        let delays: Vec<_> = (0..CONSISTENCY_RETRIES + 1)
+            .map(|attempt| retry_delay(StatusCode::PRECONDITION_FAILED, &headers, attempt))
+            .collect();
+        assert_eq!(
+            delays,
+            [40, 80, 160, 320, 640]
+                .map(|ms| Some(Duration::from_millis(ms)))
+                .into_iter()
+                .chain([None])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn retry_delay_follows_retry_after_on_429_and_503() {
+        for status in [
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::SERVICE_UNAVAILABLE,
+        ] {
+            assert_eq!(
+                retry_delay(status, &retry_after(" 2 "), 0),
+                Some(Duration::from_secs(2))
+            );
+            assert_eq!(
+                retry_delay(status, &retry_after("30"), THROTTLE_RETRIES - 1),
+                Some(MAX_RETRY_AFTER)
+            );
+            assert_eq!(
+                retry_delay(status, &retry_after("2"), THROTTLE_RETRIES),
+                None
+            );
+            assert_eq!(retry_delay(status, &retry_after("31"), 0), None);
+            assert_eq!(
+                retry_delay(status, &retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), 0),
+                None
+            );
+            assert_eq!(retry_delay(status, &HeaderMap::new(), 0), None);
+        }
+    }
+
+    #[test]
+    fn retry_delay_ignores_other_statuses() {
+        for status in [
+            StatusCode::OK,
+            StatusCode::FORBIDDEN,
+            StatusCode::NOT_FOUND,
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ] {
+            assert_eq!(retry_delay(status, &retry_after("1"), 0), None);
         }
     }
 }
@@ -0,0 +1,110 @@
+//! Description of the request `collect` sends, for dry runs and tests.
+
+use std::fmt;
+use std::sync::PoisonError;
+
+use config::ConfigError;
+
+use crate::VaultSource;
+
+/// Placeholder that replaces the token in a [`RequestPlan`].
+const REDACTED: &str = "<redacted>";
+
+/// The read request `collect` sends to Vault, with the token redacted.
+///
+/// Created with [`VaultSource::request_plan`]. Nothing is sent to build it, so it can
+/// be logged in a dry run or checked in tests. Retries of throttled requests repeat
+/// the same request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestPlan {
+    /// HTTP method, always `"GET"` for a read.
+    pub method: &'static str,
+    /// Complete URL of the read API.
+    pub url: String,
+    /// Headers in the order they are sent, with `X-Vault-Token` set to `<redacted>`.
+    pub headers: Vec<(String, String)>,
+    /// Whether an auth method logs in before the read, to obtain the token.
+    pub login: bool,
+}
+
+impl fmt::Display for RequestPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.login {
+            writeln!(f, "(login with the auth method)")?;
+        }
+        write!(f, "{} {}", self.method, self.url)?;
+        for (name, value) in &self.headers {
+            write!(f, "\n{}: {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl VaultSource {
+    /// Describes the read request `collect` would send right now, without sending it.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RequestPlan, ConfigError>` - The plan, or an error if the read URL
+    ///   cannot be built from the address, mount and path
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use config_vault::VaultSource;
+    ///
+    /// let source = VaultSource::builder()
+    ///     .addr("https://vault.example.com:8200")
+    ///     .token("hvs.EXAMPLE_TOKEN")
+    ///     .namespace("team-a")
+    ///     .mount("secret")
+    ///     .path("app/prod")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let plan = source.request_plan().unwrap();
+    /// assert_eq!(plan.url, "https://vault.example.com:8200/v1/secret/data/app/prod");
+    /// assert_eq!(
+    ///     plan.headers,
+    ///     [
+    ///         ("X-Vault-Token".to_string(), "<redacted>".to_string()),
+    ///         ("X-Vault-Namespace".to_string(), "team-a".to_string()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn request_plan(&self) -> Result<RequestPlan, ConfigError> {
+        #[cfg(feature = "blocking")]
+        let login = self.auth.is_some();
+        #[cfg(not(feature = "blocking"))]
+        let login = false;
+
+        Ok(RequestPlan {
+            method: "GET",
+            url: self.build_read_url()?.to_string(),
+            headers: self.request_headers(Some(REDACTED)),
+            login,
+        })
+    }
+
+    /// Headers sent with every request: the token (`None` for logins), namespace,
+    /// audit headers and the last `X-Vault-Index` seen.
+    pub(crate) fn request_headers(&self, token: Option<&str>) -> Vec<(String, String)> {
+        let mut headers = Vec::new();
+        if let Some(token) = token {
+            headers.push(("X-Vault-Token".to_string(), token.to_string()));
+        }
+        if let Some(namespace) = &self.namespace {
+            headers.push(("X-Vault-Namespace".to_string(), namespace.clone()));
+        }
+        headers.extend(self.audit_headers.iter().cloned());
+        if let Some(index) = self
+            .vault_index
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_deref()
+        {
+            headers.push(("X-Vault-Index".to_string(), index.to_string()));
+        }
+        headers
+    }
+}
@@ -0,0 +1,118 @@
+//! Explanation of which layer wins for every key of a layered configuration.
+//!
+//! When files, the environment and Vault all contribute to a `Config`, it is not
+//! always obvious why a key has the value it has. [`explain`] collects every layer,
+//! merges them like `Config::builder` does and reports, for each key, the effective
+//! value, the layer it came from and the layers it overrides.
+
+use std::collections::BTreeSet;
+
+use config::{Config, ConfigError, Map, Source, Value, ValueKind};
+
+use crate::diff::key_paths;
+use crate::CollectedSource;
+
+/// Where the effective value of a key comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyPrecedence {
+    /// Dotted key path (e.g. `database.password`).
+    pub key: String,
+    /// Value in the merged configuration.
+    pub value: Value,
+    /// Name of the layer that set the value.
+    pub winner: String,
+    /// Names of earlier layers that also set the key, in order.
+    pub overridden: Vec<String>,
+}
+
+/// Collects the named layers, lowest precedence first, and explains every key of the
+/// merged configuration.
+///
+/// Each layer is collected once, so a `VaultSource` layer makes a single request.
+/// Entries are sorted by key. The origin recorded by the winning layer (e.g.
+/// `vault://secret/app#db_password`) stays available on `value`.
+///
+/// # Example
+///
+/// ```
+/// use config::{Environment, File, FileFormat, Map, Source, Value};
+/// use config_vault::precedence::explain;
+/// use config_vault::CollectedSource;
+///
+/// let file = File::from_str("db_user = \"app\"\ndb_password = \"dev\"", FileFormat::Toml);
+/// let env = Environment::with_prefix("APP").source(Some(
+///     [("APP_DB_USER".to_string(), "ops".to_string())].into(),
+/// ));
+/// // Stands in for a `VaultSource`.
+/// let mut secret = Map::new();
+/// secret.insert("db_password".to_string(), Value::from("s3cr3t"));
+///
+/// let layers: Vec<(&str, Box<dyn Source + Send + Sync>)> = vec![
+///     ("defaults.toml", Box::new(file)),
+///     ("environment", Box::new(env)),
+///     ("vault", Box::new(CollectedSource::new(secret))),
+/// ];
+/// let report = explain(layers)?;
+///
+/// assert_eq!(report[0].key, "db_password");
+/// assert_eq!(report[0].winner, "vault");
+/// assert_eq!(report[0].overridden, vec!["defaults.toml".to_string()]);
+/// assert_eq!(report[1].key, "db_user");
+/// assert_eq!(report[1].winner, "environment");
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+pub fn explain<N, I>(layers: I) -> Result<Vec<KeyPrecedence>, ConfigError>
+where
+    N: Into<String>,
+    I: IntoIterator<Item = (N, Box<dyn Source + Send + Sync>)>,
+{
+    let mut builder = Config::builder();
+    let mut layer_keys: Vec<(String, BTreeSet<String>)> = Vec::new();
+
+    for (name, source) in layers {
+        let values = source.collect()?;
+        // Normalize the layer the way `Config` does (dotted keys, case) before listing
+        // its keys, so they match the keys of the merged configuration.
+        let layer = Config::builder()
+            .add_source(CollectedSource::new(values.clone()))
+            .build()?;
+        layer_keys.push((name.into(), key_paths(&layer.cache.into_table()?, false)));
+        builder = builder.add_source(CollectedSource::new(values));
+    }
+
+    let merged = builder.build()?.cache.into_table()?;
+    let mut report = Vec::new();
+
+    for key in key_paths(&merged, false) {
+        let mut overridden: Vec<String> = layer_keys
+            .iter()
+            .filter(|(_, keys)| keys.contains(&key))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let Some(winner) = overridden.pop() else {
+            continue;
+        };
+
+        report.push(KeyPrecedence {
+            value: lookup(&merged, &key).cloned().unwrap_or_default(),
+            key,
+            winner,
+            overridden,
+        });
+    }
+
+    Ok(report)
+}
+
+/// Returns the value at a dotted key path.
+pub(crate) fn lookup<'a>(table: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    let mut segments = key.split('.');
+    let mut value = table.get(segments.next()?)?;
+    for segment in segments {
+        value = match &value.kind {
+            ValueKind::Table(table) => table.get(segment)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
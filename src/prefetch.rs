@@ -0,0 +1,158 @@
+//! Background fetching of Vault secrets.
+//!
+//! [`VaultSource::prefetch`](crate::VaultSource::prefetch) starts the Vault request on a
+//! separate thread so applications can kick it off early and merge the result later.
+
+#[cfg(feature = "blocking")]
+use std::thread::JoinHandle;
+
+use config::{ConfigError, Map, Source, Value};
+
+use crate::fingerprint::{self, FingerprintPolicy};
+use crate::render::{self, Masking, RenderFormat, Renderer};
+#[cfg(feature = "blocking")]
+use crate::VaultSource;
+
+#[cfg(feature = "blocking")]
+impl VaultSource {
+    /// Starts fetching the secret on a background thread.
+    ///
+    /// This lets applications kick off Vault requests early in `main()` and keep
+    /// initializing other components. Joining the returned handle yields a
+    /// [`CollectedSource`] that can be merged into a `Config` without blocking on
+    /// the network again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use config::Config;
+    /// use config_vault::VaultSource;
+    ///
+    /// let source = VaultSource::new(
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     "secret",
+    ///     "dev",
+    /// );
+    /// let prefetch = source.prefetch();
+    ///
+    /// // ... other initialization work ...
+    ///
+    /// let config = Config::builder()
+    ///     .add_source(prefetch.join()?)
+    ///     .build()?;
+    /// # Ok::<(), config::ConfigError>(())
+    /// ```
+    pub fn prefetch(&self) -> PrefetchHandle {
+        let source = self.clone();
+        PrefetchHandle::new(std::thread::spawn(move || source.collect()))
+    }
+}
+
+/// Handle to a secret being fetched in the background.
+///
+/// Returned by [`VaultSource::prefetch`](crate::VaultSource::prefetch).
+#[cfg(feature = "blocking")]
+#[derive(Debug)]
+pub struct PrefetchHandle {
+    handle: JoinHandle<Result<Map<String, Value>, ConfigError>>,
+}
+
+#[cfg(feature = "blocking")]
+impl PrefetchHandle {
+    pub(crate) fn new(handle: JoinHandle<Result<Map<String, Value>, ConfigError>>) -> Self {
+        Self { handle }
+    }
+
+    /// Returns `true` once the background fetch has completed.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Waits for the background fetch and returns the collected values as a `Source`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CollectedSource, ConfigError>` - A source ready to be merged into a
+    ///   `Config`, or the error the fetch failed with.
+    pub fn join(self) -> Result<CollectedSource, ConfigError> {
+        let values = self
+            .handle
+            .join()
+            .map_err(|_| ConfigError::Message("Vault prefetch thread panicked".into()))??;
+
+        Ok(CollectedSource::new(values))
+    }
+}
+
+/// An in-memory `Source` holding values already collected from Vault.
+///
+/// Adding it to a `Config` builder never touches the network.
+///
+/// # Example
+///
+/// ```
+/// use config::{Config, Map, Value};
+/// use config_vault::CollectedSource;
+///
+/// let mut values = Map::new();
+/// values.insert("port".to_string(), Value::from(8080));
+///
+/// let config = Config::builder()
+///     .add_source(CollectedSource::new(values))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(config.get_int("port").unwrap(), 8080);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CollectedSource {
+    values: Map<String, Value>,
+}
+
+impl CollectedSource {
+    /// Creates a source from already collected values.
+    pub fn new(values: Map<String, Value>) -> Self {
+        Self { values }
+    }
+
+    /// Returns the collected values.
+    pub fn values(&self) -> &Map<String, Value> {
+        &self.values
+    }
+
+    /// Returns a stable fingerprint of the collected values.
+    ///
+    /// See [`fingerprint::fingerprint`] for details.
+    pub fn fingerprint(&self, policy: FingerprintPolicy) -> String {
+        fingerprint::fingerprint(&self.values, policy)
+    }
+
+    /// Renders the collected values as a JSON, TOML or YAML document.
+    ///
+    /// See [`render::render`] for details.
+    pub fn render(&self, format: RenderFormat, masking: Masking) -> Result<String, ConfigError> {
+        render::render(&self.values, format, masking)
+    }
+
+    /// Renders the collected values with a custom [`Renderer`].
+    ///
+    /// See [`render::render_with`] for details.
+    pub fn render_with(
+        &self,
+        renderer: &dyn Renderer,
+        masking: Masking,
+    ) -> Result<String, ConfigError> {
+        render::render_with(&self.values, renderer, masking)
+    }
+}
+
+impl Source for CollectedSource {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        Ok(self.values.clone())
+    }
+}
@@ -0,0 +1,97 @@
+//! Tracking of where each configuration value came from.
+//!
+//! Every value loaded by a `VaultSource` carries an origin URI such as
+//! `vault://secret/app/prod#db_password`. The `config` crate keeps origins through
+//! merging, so [`provenance`] can answer "where is this value set" for any key of the
+//! final `Config`, including keys from files or the environment.
+
+use config::{Config, Value, ValueKind};
+
+/// Returns the origin of the value stored under `key` in `config`.
+///
+/// `key` uses the same dotted path syntax as `Config::get`, with `[n]` for array items.
+/// Values loaded from Vault report `vault://<mount>/<path>#<key>`, where `<key>` is
+/// the key as stored in Vault, before flattening, renames or key case; keys added while
+/// loading, such as computed keys, report their own name. Values from other sources
+/// report whatever origin they recorded (e.g. a file path).
+///
+/// # Example
+///
+/// ```
+/// use config::{Config, Map, Value};
+/// use config_vault::{provenance, CollectedSource};
+///
+/// let origin = "vault://secret/app/prod#db_password".to_string();
+/// let mut values = Map::new();
+/// values.insert("password".to_string(), Value::new(Some(&origin), "s3cr3t"));
+///
+/// let config = Config::builder()
+///     .add_source(CollectedSource::new(values))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(provenance(&config, "password").as_deref(), Some(origin.as_str()));
+/// ```
+pub fn provenance(config: &Config, key: &str) -> Option<String> {
+    let mut value = &config.cache;
+
+    for segment in key.split('.') {
+        let (name, indexes) = match segment.find('[') {
+            Some(start) => (&segment[..start], &segment[start..]),
+            None => (segment, ""),
+        };
+
+        if !name.is_empty() {
+            value = match &value.kind {
+                ValueKind::Table(table) => table.get(name)?,
+                _ => return None,
+            };
+        }
+
+        for index in indexes.split(']').filter(|s| !s.is_empty()) {
+            let index: usize = index.strip_prefix('[')?.parse().ok()?;
+            value = match &value.kind {
+                ValueKind::Array(items) => items.get(index)?,
+                _ => return None,
+            };
+        }
+    }
+
+    value.origin().map(str::to_string)
+}
+
+/// Tags `value`, and every value nested in it, with an origin below `origin`.
+///
+/// Nested values extend the fragment with their key path (`#database.password`).
+pub(crate) fn with_origin(value: Value, origin: &str) -> Value {
+    let kind = match value.kind {
+        ValueKind::Table(table) => ValueKind::Table(
+            table
+                .into_iter()
+                .map(|(key, value)| {
+                    let nested = with_origin(value, &format!("{}.{}", origin, key));
+                    (key, nested)
+                })
+                .collect(),
+        ),
+        ValueKind::Array(items) => ValueKind::Array(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| with_origin(value, &format!("{}[{}]", origin, index)))
+                .collect(),
+        ),
+        kind => kind,
+    };
+
+    Value::new(Some(&origin.to_string()), kind)
+}
+
+/// Tags `value` like [`with_origin`], unless it already has an origin.
+pub(crate) fn with_default_origin(value: Value, origin: &str) -> Value {
+    if value.origin().is_some() {
+        value
+    } else {
+        with_origin(value, origin)
+    }
+}
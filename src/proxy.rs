@@ -0,0 +1,140 @@
+//! Proxy settings of the clients used to talk to Vault.
+
+use std::fmt;
+
+use config::ConfigError;
+use reqwest::Proxy;
+use url::Url;
+
+use crate::diagnostics;
+
+/// How requests to Vault are routed through HTTP or SOCKS proxies.
+#[derive(Clone, Default)]
+pub(crate) enum ProxySetting {
+    /// Use the proxy environment variables (`HTTPS_PROXY`, `ALL_PROXY`, `NO_PROXY`...).
+    #[default]
+    System,
+    /// Send every request through this proxy, ignoring the environment.
+    Url {
+        url: String,
+        credentials: Option<(String, String)>,
+    },
+    /// Connect directly, ignoring the environment.
+    Direct,
+}
+
+impl ProxySetting {
+    /// Returns the explicit proxy, if any.
+    pub(crate) fn proxy(&self) -> Result<Option<Proxy>, ConfigError> {
+        let ProxySetting::Url { url, credentials } = self else {
+            return Ok(None);
+        };
+        check_scheme(url, credentials.is_some())?;
+        let mut proxy = Proxy::all(url.as_str()).map_err(|e| {
+            ConfigError::Message(format!(
+                "Invalid proxy URL '{}': {}",
+                redact(url),
+                diagnostics::root_cause(&e)
+            ))
+        })?;
+        if let Some((username, password)) = credentials {
+            proxy = proxy.basic_auth(username, password);
+        }
+        Ok(Some(proxy))
+    }
+
+    /// Describes the proxy in effect for connection diagnostics, or `None` if
+    /// requests go out directly.
+    pub(crate) fn describe(&self) -> Option<String> {
+        match self {
+            ProxySetting::System => system_proxies(),
+            ProxySetting::Url { url, .. } => {
+                Some(format!("Requests go through the proxy {}", redact(url)))
+            }
+            ProxySetting::Direct => None,
+        }
+    }
+}
+
+impl fmt::Debug for ProxySetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxySetting::System => f.write_str("System"),
+            ProxySetting::Url { url, credentials } => f
+                .debug_struct("Url")
+                .field("url", &redact(url))
+                .field("credentials", &credentials.as_ref().map(|_| "<redacted>"))
+                .finish(),
+            ProxySetting::Direct => f.write_str("Direct"),
+        }
+    }
+}
+
+/// Rejects proxy URLs reqwest would refuse with a bare "unknown proxy scheme" or
+/// panic on: SOCKS without the `socks` feature and SOCKS4 with credentials.
+fn check_scheme(url: &str, has_credentials: bool) -> Result<(), ConfigError> {
+    let Ok(parsed) = Url::parse(url) else {
+        return Ok(());
+    };
+    match parsed.scheme() {
+        "socks4" | "socks4a" | "socks5" | "socks5h" if !cfg!(feature = "socks") => {
+            Err(ConfigError::Message(format!(
+                "Proxy '{}' is a SOCKS proxy; enable the `socks` feature of config-vault",
+                redact(url)
+            )))
+        }
+        "socks4" | "socks4a" if has_credentials || parsed.password().is_some() => {
+            Err(ConfigError::Message(format!(
+                "SOCKS4 proxy '{}' does not support credentials; use socks5:// or socks5h://",
+                redact(url)
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Environment variables reqwest reads its proxy settings from.
+const PROXY_VARS: [&str; 6] = [
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// Reports the proxy environment variables reqwest picks up.
+fn system_proxies() -> Option<String> {
+    let proxies: Vec<String> = PROXY_VARS
+        .iter()
+        .filter_map(|var| {
+            std::env::var(var)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(|v| format!("{}={}", var, redact(&v)))
+        })
+        .collect();
+    if proxies.is_empty() {
+        return None;
+    }
+
+    let no_proxy = std::env::var("NO_PROXY")
+        .or_else(|_| std::env::var("no_proxy"))
+        .unwrap_or_default();
+    Some(format!(
+        "Requests may go through a proxy ({}; NO_PROXY={:?})",
+        proxies.join(", "),
+        no_proxy
+    ))
+}
+
+/// Hides the password of a proxy URL with credentials.
+pub(crate) fn redact(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("****"));
+            parsed.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
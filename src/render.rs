@@ -0,0 +1,301 @@
+//! Rendering of collected configuration as JSON, TOML or YAML documents.
+//!
+//! Useful to generate bootstrap files for non-Rust components from the same Vault
+//! secrets an application loads. Other formats plug in through the [`Renderer`]
+//! trait; with the `age` feature, [`AgeRenderer`] encrypts the document to age
+//! recipients for a GitOps workflow.
+
+#[cfg(feature = "age")]
+use std::fmt;
+
+use config::{ConfigError, Map, Value, ValueKind};
+use serde_json::Value as JsonValue;
+use yaml_rust2::{Yaml, YamlEmitter};
+
+use crate::diff::expand_keys;
+
+/// Placeholder written instead of values when masking is enabled.
+const MASK: &str = "********";
+
+/// Document format produced by [`render`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+/// Whether values are written out or hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Masking {
+    /// Write the actual values.
+    None,
+    /// Replace every scalar value with a placeholder, keeping the structure.
+    Values,
+}
+
+/// Renders `values` as a configuration file in the given format.
+///
+/// Keys are written in sorted order so the output is stable across runs. Dotted keys,
+/// as produced by [`VaultSource::set_nested_at`](crate::VaultSource::set_nested_at)
+/// or a key rename, are written as the nested tables `config` turns them into.
+///
+/// # Example
+///
+/// ```
+/// use config::{Map, Value};
+/// use config_vault::render::{render, Masking, RenderFormat};
+///
+/// let mut values = Map::new();
+/// values.insert("user".to_string(), Value::from("app"));
+/// values.insert("port".to_string(), Value::from(5432));
+///
+/// let toml = render(&values, RenderFormat::Toml, Masking::None).unwrap();
+/// assert_eq!(toml, "port = 5432\nuser = \"app\"\n");
+///
+/// let masked = render(&values, RenderFormat::Json, Masking::Values).unwrap();
+/// assert_eq!(masked, r#"{"port":"********","user":"********"}"#);
+///
+/// let mut dotted = Map::new();
+/// dotted.insert("database.user".to_string(), Value::from("app"));
+/// let json = render(&dotted, RenderFormat::Json, Masking::None).unwrap();
+/// assert_eq!(json, r#"{"database":{"user":"app"}}"#);
+/// ```
+pub fn render(
+    values: &Map<String, Value>,
+    format: RenderFormat,
+    masking: Masking,
+) -> Result<String, ConfigError> {
+    render_with(values, &format, masking)
+}
+
+/// Writes the document of the collected values in a format.
+///
+/// [`RenderFormat`] implements it for the built-in formats.
+///
+/// # Example
+///
+/// A renderer for `.env` files:
+///
+/// ```
+/// use config::{ConfigError, Map, Value};
+/// use config_vault::render::{render_with, Masking, Renderer};
+/// use serde_json::Value as JsonValue;
+///
+/// struct DotEnv;
+///
+/// impl Renderer for DotEnv {
+///     fn render(&self, document: &JsonValue) -> Result<String, ConfigError> {
+///         let mut output = String::new();
+///         for (key, value) in document.as_object().into_iter().flatten() {
+///             let value = match value {
+///                 JsonValue::String(s) => s.clone(),
+///                 other => other.to_string(),
+///             };
+///             output.push_str(&format!("{}={}\n", key.to_uppercase(), value));
+///         }
+///         Ok(output)
+///     }
+/// }
+///
+/// let mut values = Map::new();
+/// values.insert("user".to_string(), Value::from("app"));
+/// values.insert("port".to_string(), Value::from(5432));
+///
+/// let env = render_with(&values, &DotEnv, Masking::None).unwrap();
+/// assert_eq!(env, "PORT=5432\nUSER=app\n");
+/// ```
+pub trait Renderer {
+    /// Writes `document`, a JSON object with the collected values (masked if
+    /// requested) and its keys in sorted order.
+    fn render(&self, document: &JsonValue) -> Result<String, ConfigError>;
+}
+
+impl Renderer for RenderFormat {
+    fn render(&self, document: &JsonValue) -> Result<String, ConfigError> {
+        match self {
+            RenderFormat::Json => Ok(document.to_string()),
+            RenderFormat::Toml => {
+                toml::to_string(&to_toml(document)?).map_err(|e| ConfigError::Foreign(Box::new(e)))
+            }
+            RenderFormat::Yaml => {
+                let mut output = String::new();
+                YamlEmitter::new(&mut output)
+                    .dump(&to_yaml(document))
+                    .map_err(|e| ConfigError::Message(format!("Cannot render YAML: {}", e)))?;
+                output.push('\n');
+                Ok(output)
+            }
+        }
+    }
+}
+
+/// Encrypts the document of another [`Renderer`] to age recipients.
+///
+/// The output is an ASCII-armored age file that any of the recipients can decrypt,
+/// e.g. with `age --decrypt -i key.txt`. This keeps a rendered snapshot of the
+/// secrets safe to commit to a GitOps repository.
+///
+/// # Example
+///
+/// ```
+/// use config::{Map, Value};
+/// use config_vault::render::{render_with, AgeRenderer, Masking, RenderFormat};
+///
+/// let identity = age::x25519::Identity::generate();
+/// let renderer = AgeRenderer::new(RenderFormat::Toml, vec![identity.to_public()]);
+///
+/// let mut values = Map::new();
+/// values.insert("user".to_string(), Value::from("app"));
+///
+/// let encrypted = render_with(&values, &renderer, Masking::None).unwrap();
+/// assert!(encrypted.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+///
+/// let decrypted = age::decrypt(&identity, encrypted.as_bytes()).unwrap();
+/// assert_eq!(decrypted, b"user = \"app\"\n");
+/// ```
+#[cfg(feature = "age")]
+#[derive(Clone)]
+pub struct AgeRenderer<R = RenderFormat> {
+    inner: R,
+    recipients: Vec<age::x25519::Recipient>,
+}
+
+#[cfg(feature = "age")]
+impl<R: Renderer> AgeRenderer<R> {
+    /// Encrypts the output of `inner` to `recipients`. Recipients parse from their
+    /// `age1...` public key.
+    pub fn new(inner: R, recipients: Vec<age::x25519::Recipient>) -> Self {
+        Self { inner, recipients }
+    }
+}
+
+#[cfg(feature = "age")]
+impl<R: Renderer> Renderer for AgeRenderer<R> {
+    fn render(&self, document: &JsonValue) -> Result<String, ConfigError> {
+        use std::io::Write;
+
+        use age::armor::{ArmoredWriter, Format};
+
+        let plaintext = self.inner.render(document)?;
+        let encryptor = age::Encryptor::with_recipients(
+            self.recipients
+                .iter()
+                .map(|recipient| recipient as &dyn age::Recipient),
+        )
+        .map_err(|e| {
+            ConfigError::Message(format!("Cannot encrypt the rendered configuration: {}", e))
+        })?;
+
+        let mut output = Vec::new();
+        let encrypt = || -> std::io::Result<()> {
+            let armored = ArmoredWriter::wrap_output(&mut output, Format::AsciiArmor)?;
+            let mut writer = encryptor.wrap_output(armored)?;
+            writer.write_all(plaintext.as_bytes())?;
+            writer.finish()?.finish()?;
+            Ok(())
+        };
+        encrypt().map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+
+        Ok(String::from_utf8(output).expect("armored age output is ASCII"))
+    }
+}
+
+#[cfg(feature = "age")]
+impl<R: fmt::Debug> fmt::Debug for AgeRenderer<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let recipients: Vec<String> = self.recipients.iter().map(|r| r.to_string()).collect();
+        f.debug_struct("AgeRenderer")
+            .field("inner", &self.inner)
+            .field("recipients", &recipients)
+            .finish()
+    }
+}
+
+/// Renders `values` with a custom [`Renderer`].
+pub fn render_with(
+    values: &Map<String, Value>,
+    renderer: &dyn Renderer,
+    masking: Masking,
+) -> Result<String, ConfigError> {
+    let document = JsonValue::Object(
+        expand_keys(values)
+            .iter()
+            .map(|(key, value)| (key.clone(), to_json(value, masking)))
+            .collect(),
+    );
+
+    renderer.render(&document)
+}
+
+fn to_json(value: &Value, masking: Masking) -> JsonValue {
+    match &value.kind {
+        ValueKind::Table(table) => JsonValue::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.clone(), to_json(value, masking)))
+                .collect(),
+        ),
+        ValueKind::Array(items) => {
+            JsonValue::Array(items.iter().map(|item| to_json(item, masking)).collect())
+        }
+        _ if masking == Masking::Values => JsonValue::from(MASK),
+        ValueKind::Nil => JsonValue::Null,
+        ValueKind::Boolean(b) => JsonValue::from(*b),
+        ValueKind::I64(i) => JsonValue::from(*i),
+        ValueKind::I128(i) => i64::try_from(*i)
+            .map(JsonValue::from)
+            .unwrap_or_else(|_| JsonValue::from(i.to_string())),
+        ValueKind::U64(u) => JsonValue::from(*u),
+        ValueKind::U128(u) => u64::try_from(*u)
+            .map(JsonValue::from)
+            .unwrap_or_else(|_| JsonValue::from(u.to_string())),
+        ValueKind::Float(f) => JsonValue::from(*f),
+        ValueKind::String(s) => JsonValue::from(s.as_str()),
+    }
+}
+
+/// Converts to a TOML value directly: with `serde_json/arbitrary_precision`, serializing
+/// a JSON number through serde produces a private wrapper struct instead of a number.
+fn to_toml(value: &JsonValue) -> Result<toml::Value, ConfigError> {
+    Ok(match value {
+        JsonValue::Null => {
+            return Err(ConfigError::Message(
+                "Cannot render null values as TOML".into(),
+            ))
+        }
+        JsonValue::Bool(b) => toml::Value::Boolean(*b),
+        JsonValue::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => toml::Value::Integer(i),
+            (None, Some(f)) => toml::Value::Float(f),
+            (None, None) => toml::Value::String(n.to_string()),
+        },
+        JsonValue::String(s) => toml::Value::String(s.clone()),
+        JsonValue::Array(items) => {
+            toml::Value::Array(items.iter().map(to_toml).collect::<Result<_, _>>()?)
+        }
+        JsonValue::Object(obj) => toml::Value::Table(
+            obj.iter()
+                .map(|(key, value)| Ok((key.clone(), to_toml(value)?)))
+                .collect::<Result<_, ConfigError>>()?,
+        ),
+    })
+}
+
+fn to_yaml(value: &JsonValue) -> Yaml {
+    match value {
+        JsonValue::Null => Yaml::Null,
+        JsonValue::Bool(b) => Yaml::Boolean(*b),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        JsonValue::String(s) => Yaml::String(s.clone()),
+        JsonValue::Array(items) => Yaml::Array(items.iter().map(to_yaml).collect()),
+        JsonValue::Object(obj) => Yaml::Hash(
+            obj.iter()
+                .map(|(key, value)| (Yaml::String(key.clone()), to_yaml(value)))
+                .collect(),
+        ),
+    }
+}
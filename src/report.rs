@@ -0,0 +1,85 @@
+//! Summaries of the secrets loaded by a `VaultSource`.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, AGE};
+
+/// Summary of a single secret load, without any secret values.
+///
+/// Passed to the callback registered with
+/// [`VaultSource::set_report_callback`](crate::VaultSource::set_report_callback)
+/// after every successful `collect`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Vault address the secret was read from.
+    pub addr: String,
+    /// Mount and path of the secret (e.g. "secret/app/prod").
+    pub path: String,
+    /// Number of keys loaded into the configuration.
+    pub keys: usize,
+    /// Version of the secret, when the engine is versioned (KV2).
+    pub version: Option<u64>,
+    /// Time spent fetching and processing the secret.
+    pub duration: Duration,
+    /// Cache status reported by a caching Vault Agent or Vault Proxy in front of
+    /// Vault; `None` when the response did not come through one.
+    pub agent_cache: Option<AgentCacheStatus>,
+}
+
+/// Whether a caching Vault Agent (or Vault Proxy) answered from its cache, from the
+/// `X-Cache` and `Age` headers it adds to responses.
+///
+/// An agent hit can be as old as the agent's cache allows, so applications that need
+/// the latest version of a secret can check [`LoadReport::agent_cache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgentCacheStatus {
+    /// `true` for `X-Cache: HIT`, `false` for `X-Cache: MISS`.
+    pub hit: bool,
+    /// Time since the agent cached the response, from the `Age` header.
+    pub age: Option<Duration>,
+}
+
+impl AgentCacheStatus {
+    /// Reads the cache headers of a response, if an agent added them.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let hit = match headers.get("X-Cache")?.to_str().ok()?.trim() {
+            status if status.eq_ignore_ascii_case("HIT") => true,
+            status if status.eq_ignore_ascii_case("MISS") => false,
+            _ => return None,
+        };
+        let age = headers
+            .get(AGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_secs);
+        Some(Self { hit, age })
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "loaded {} keys from {}", self.keys, self.path)?;
+        if let Some(version) = self.version {
+            write!(f, " (version {})", version)?;
+        }
+        if let Some(AgentCacheStatus { hit: true, age }) = self.agent_cache {
+            match age {
+                Some(age) => write!(f, " from the agent cache ({}s old)", age.as_secs())?,
+                None => write!(f, " from the agent cache")?,
+            }
+        }
+        write!(f, " in {:?}", self.duration)
+    }
+}
+
+/// Callback invoked with the [`LoadReport`] of each successful load.
+#[derive(Clone)]
+pub(crate) struct ReportCallback(pub(crate) Arc<dyn Fn(&LoadReport) + Send + Sync>);
+
+impl fmt::Debug for ReportCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ReportCallback")
+    }
+}
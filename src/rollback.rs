@@ -0,0 +1,110 @@
+//! Rollback of KV2 secrets to a previous version.
+//!
+//! This module is compiled out by the `read-only` feature.
+
+use config::ConfigError;
+use serde_json::json;
+
+use crate::{EngineKind, VaultSource};
+
+impl VaultSource {
+    /// Rolls the KV2 secret back to a previous version.
+    ///
+    /// The data of `version` is written as a new version of the secret, guarded with
+    /// check-and-set against the current version so concurrent writers are not
+    /// overwritten. If `version` was soft-deleted it is undeleted first; destroyed
+    /// versions cannot be restored.
+    ///
+    /// This method is not available with the `read-only` feature.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64, ConfigError>` - The version number created by the rollback
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use config_vault::VaultSource;
+    ///
+    /// let source = VaultSource::new(
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     "secret",
+    ///     "dev",
+    /// );
+    /// let new_version = source.rollback_to_version(3)?;
+    /// # Ok::<(), config::ConfigError>(())
+    /// ```
+    pub fn rollback_to_version(&self, version: u64) -> Result<u64, ConfigError> {
+        if self.engine != EngineKind::Kv2 || self.custom_endpoint.is_some() {
+            return Err(ConfigError::Message(
+                "Rollback is only supported by the KV2 engine".into(),
+            ));
+        }
+
+        let client = self.http_client()?;
+        let token = self.resolve_token(&client)?;
+        let token = Some(token.as_str());
+        let metadata_url = self.build_api_url(&format!(
+            "v1/{}/metadata/{}",
+            self.vault_mount, self.vault_path
+        ))?;
+        let data_url = self.build_kv_read_url()?;
+
+        let metadata = self.send(client.get(metadata_url), token)?;
+        let current_version = metadata
+            .pointer("/data/current_version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| ConfigError::Message("Missing current_version in metadata".into()))?;
+        let target = metadata
+            .pointer(&format!("/data/versions/{}", version))
+            .ok_or_else(|| ConfigError::Message(format!("Secret has no version {}", version)))?;
+
+        if target.get("destroyed").and_then(|v| v.as_bool()) == Some(true) {
+            return Err(ConfigError::Message(format!(
+                "Version {} was destroyed and cannot be restored",
+                version
+            )));
+        }
+
+        if target
+            .get("deletion_time")
+            .and_then(|v| v.as_str())
+            .is_some_and(|t| !t.is_empty())
+        {
+            let undelete_url = self.build_api_url(&format!(
+                "v1/{}/undelete/{}",
+                self.vault_mount, self.vault_path
+            ))?;
+            self.send(
+                client
+                    .post(undelete_url)
+                    .json(&json!({ "versions": [version] })),
+                token,
+            )?;
+        }
+
+        let mut read_url = data_url.clone();
+        read_url
+            .query_pairs_mut()
+            .append_pair("version", &version.to_string());
+        let old = self.send(client.get(read_url), token)?;
+        let data = old
+            .pointer("/data/data")
+            .cloned()
+            .ok_or_else(|| ConfigError::Message(format!("Version {} has no data", version)))?;
+
+        let written = self.send(
+            client.post(data_url).json(&json!({
+                "options": { "cas": current_version },
+                "data": data,
+            })),
+            token,
+        )?;
+
+        written
+            .pointer("/data/version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| ConfigError::Message("Missing version in write response".into()))
+    }
+}
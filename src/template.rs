@@ -0,0 +1,296 @@
+//! Lightweight `{{ key }}` interpolation between values of the same secret.
+
+use std::collections::HashMap;
+
+use config::{Map, Value, ValueKind};
+
+use crate::validation::KeyFailure;
+
+/// Replaces every `{{ key }}` placeholder in the string values of `values` with the
+/// value stored under `key` in the same map.
+///
+/// Values nested in tables and arrays are interpolated too, and nested values are
+/// referenced by their dotted path (`{{ db.host }}`, `{{ hosts[0] }}`). Placeholders
+/// are resolved recursively, so a value may reference another templated value.
+/// Every value with an unknown key, a reference cycle or an unterminated placeholder
+/// is added to `failures` and left as it is.
+pub(crate) fn interpolate(values: &mut Map<String, Value>, failures: &mut Vec<KeyFailure>) {
+    let mut leaves = Vec::new();
+    for (key, value) in values.iter() {
+        collect_leaves(
+            key.clone(),
+            vec![Segment::Key(key.clone())],
+            value,
+            &mut leaves,
+        );
+    }
+
+    let templated: Vec<(String, Vec<Segment>)> = leaves
+        .iter()
+        .filter(|(_, _, value)| matches!(&value.kind, ValueKind::String(s) if s.contains("{{")))
+        .map(|(path, location, _)| (path.clone(), location.clone()))
+        .collect();
+    if templated.is_empty() {
+        return;
+    }
+
+    let mut index = HashMap::new();
+    for (path, _, value) in leaves {
+        index.entry(path).or_insert(value);
+    }
+
+    let mut rendered = HashMap::new();
+    for (path, _) in &templated {
+        if let Err(failure) = resolve(path, &index, &mut rendered, &mut Vec::new()) {
+            if !failures.contains(&failure) {
+                failures.push(failure);
+            }
+        }
+    }
+
+    for (path, location) in templated {
+        if let (Some(value), Some(slot)) = (rendered.remove(&path), locate(values, &location)) {
+            let origin = slot.origin().map(str::to_string);
+            *slot = Value::new(origin.as_ref(), value);
+        }
+    }
+}
+
+/// A step from a table into one of its values.
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Collects the scalar values below `value` with their dotted path and location.
+fn collect_leaves(
+    path: String,
+    location: Vec<Segment>,
+    value: &Value,
+    leaves: &mut Vec<(String, Vec<Segment>, Value)>,
+) {
+    match &value.kind {
+        ValueKind::Table(table) => {
+            for (key, child) in table {
+                let mut location = location.clone();
+                location.push(Segment::Key(key.clone()));
+                collect_leaves(format!("{}.{}", path, key), location, child, leaves);
+            }
+        }
+        ValueKind::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                let mut location = location.clone();
+                location.push(Segment::Index(i));
+                collect_leaves(format!("{}[{}]", path, i), location, child, leaves);
+            }
+        }
+        _ => leaves.push((path, location, value.clone())),
+    }
+}
+
+/// Returns the value at `location`.
+fn locate<'a>(values: &'a mut Map<String, Value>, location: &[Segment]) -> Option<&'a mut Value> {
+    let (Segment::Key(first), rest) = location.split_first()? else {
+        return None;
+    };
+    let mut value = values.get_mut(first)?;
+    for segment in rest {
+        value = match (segment, &mut value.kind) {
+            (Segment::Key(key), ValueKind::Table(table)) => table.get_mut(key)?,
+            (Segment::Index(i), ValueKind::Array(items)) => items.get_mut(*i)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+/// Resolves the final string value of `key`, memoizing the result in `rendered`.
+fn resolve(
+    key: &str,
+    values: &HashMap<String, Value>,
+    rendered: &mut HashMap<String, String>,
+    stack: &mut Vec<String>,
+) -> Result<String, KeyFailure> {
+    if let Some(done) = rendered.get(key) {
+        return Ok(done.clone());
+    }
+
+    if stack.iter().any(|k| k == key) {
+        stack.push(key.to_string());
+        return Err(KeyFailure::key(
+            key,
+            format!(
+                "is part of a cyclic template reference: {}",
+                stack.join(" -> ")
+            ),
+        ));
+    }
+
+    let value = values
+        .get(key)
+        .ok_or_else(|| KeyFailure::key(key, "is not a value that can be referenced"))?;
+
+    let template = match &value.kind {
+        ValueKind::String(s) => s.clone(),
+        _ => {
+            return value
+                .clone()
+                .into_string()
+                .map_err(|e| KeyFailure::key(key, format!("cannot be referenced: {}", e)))
+        }
+    };
+
+    stack.push(key.to_string());
+
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template.as_str();
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| KeyFailure::key(key, "has an unterminated template placeholder"))?
+            + start;
+
+        output.push_str(&rest[..start]);
+        let reference = rest[start + 2..end].trim();
+        if !values.contains_key(reference) {
+            return Err(KeyFailure::key(
+                key,
+                format!("references the unknown template key '{}'", reference),
+            ));
+        }
+        output.push_str(&resolve(reference, values, rendered, stack)?);
+        rest = &rest[end + 2..];
+    }
+    output.push_str(rest);
+
+    stack.pop();
+    rendered.insert(key.to_string(), output.clone());
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(&str, Value)]) -> Map<String, Value> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    fn render(values: &mut Map<String, Value>) -> Vec<KeyFailure> {
+        let mut failures = Vec::new();
+        interpolate(values, &mut failures);
+        failures
+    }
+
+    #[test]
+    fn references_top_level_dotted_and_indexed_keys() {
+        let mut values = table(&[
+            ("user", Value::from("app")),
+            (
+                "db",
+                Value::from(table(&[("host", Value::from("db.internal"))])),
+            ),
+            (
+                "ports",
+                Value::from(vec![Value::from(5432), Value::from(6432)]),
+            ),
+            (
+                "url",
+                Value::from("postgres://{{user}}@{{ db.host }}:{{ ports[1] }}"),
+            ),
+        ]);
+
+        assert!(render(&mut values).is_empty());
+        assert_eq!(
+            values["url"].clone().into_string().unwrap(),
+            "postgres://app@db.internal:6432"
+        );
+    }
+
+    #[test]
+    fn placeholders_inside_nested_tables_are_resolved() {
+        let mut values = table(&[
+            ("user", Value::from("app")),
+            (
+                "db",
+                Value::from(table(&[
+                    ("host", Value::from("db.internal")),
+                    ("url", Value::from("{{ user }}@{{ db.host }}")),
+                ])),
+            ),
+        ]);
+
+        assert!(render(&mut values).is_empty());
+        let db = values["db"].clone().into_table().unwrap();
+        assert_eq!(db["url"].clone().into_string().unwrap(), "app@db.internal");
+    }
+
+    #[test]
+    fn templated_values_may_reference_each_other() {
+        let mut values = table(&[
+            ("host", Value::from("db.internal")),
+            ("addr", Value::from("{{ host }}:5432")),
+            ("url", Value::from("postgres://{{ addr }}")),
+        ]);
+
+        assert!(render(&mut values).is_empty());
+        assert_eq!(
+            values["url"].clone().into_string().unwrap(),
+            "postgres://db.internal:5432"
+        );
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let mut values = table(&[("a", Value::from("{{ b }}")), ("b", Value::from("{{ a }}"))]);
+
+        let mut failures = render(&mut values);
+        failures.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(
+            failures,
+            vec![
+                KeyFailure::key("a", "is part of a cyclic template reference: a -> b -> a"),
+                KeyFailure::key("b", "is part of a cyclic template reference: b -> a -> b"),
+            ]
+        );
+        assert_eq!(values["a"].clone().into_string().unwrap(), "{{ b }}");
+    }
+
+    #[test]
+    fn unknown_keys_are_reported() {
+        let mut values = table(&[("url", Value::from("postgres://{{ user }}@db"))]);
+
+        assert_eq!(
+            render(&mut values),
+            vec![KeyFailure::key(
+                "url",
+                "references the unknown template key 'user'"
+            )]
+        );
+        assert_eq!(
+            values["url"].clone().into_string().unwrap(),
+            "postgres://{{ user }}@db"
+        );
+    }
+
+    #[test]
+    fn unterminated_placeholders_are_reported() {
+        let mut values = table(&[
+            ("user", Value::from("app")),
+            ("url", Value::from("postgres://{{ user @db")),
+        ]);
+
+        assert_eq!(
+            render(&mut values),
+            vec![KeyFailure::key(
+                "url",
+                "has an unterminated template placeholder"
+            )]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Handling of secret values that are not clean text.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use config::{ConfigError, Map, Value, ValueKind};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+use crate::validation::KeyFailure;
+
+/// What to do with secret values that are not clean text.
+///
+/// A value is not clean text when it contains control characters other than tab,
+/// line feed and carriage return, or the replacement character U+FFFD, which is
+/// what remains of bytes that were not valid UTF-8 (Vault itself replaces them
+/// when a binary value is written). A response body that is not valid UTF-8 is
+/// decoded lossily, except with [`Error`](Self::Error).
+///
+/// # Example
+///
+/// ```
+/// use config_vault::{TextPolicy, VaultSource};
+///
+/// let source = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .token("hvs.EXAMPLE_TOKEN")
+///     .mount("secret")
+///     .path("dev")
+///     .text_policy(TextPolicy::Base64)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextPolicy {
+    /// Keep values as they are.
+    #[default]
+    Preserve,
+    /// Fail `collect`, naming the offending key.
+    Error,
+    /// Replace control characters with U+FFFD.
+    Replace,
+    /// Encode the whole value as standard base64.
+    Base64,
+}
+
+impl TextPolicy {
+    /// Parses a response body, applying the policy to bytes that are not UTF-8.
+    pub(crate) fn parse_body(self, body: &[u8]) -> Result<JsonValue, ConfigError> {
+        let body = match std::str::from_utf8(body) {
+            Ok(body) => body.into(),
+            Err(e) if self == TextPolicy::Error => {
+                return Err(ConfigError::Message(format!(
+                    "Vault response is not valid UTF-8: {}",
+                    e
+                )))
+            }
+            Err(_) => String::from_utf8_lossy(body),
+        };
+
+        serde_json::from_str(&body).map_err(|e| ConfigError::Foreign(Box::new(e)))
+    }
+
+    /// Applies the policy to the string values of a converted secret, including
+    /// those nested in tables and arrays. With [`Error`](Self::Error), every
+    /// offending value is added to `failures`.
+    pub(crate) fn apply(self, secret: &mut Map<String, Value>, failures: &mut Vec<KeyFailure>) {
+        if self == TextPolicy::Preserve {
+            return;
+        }
+        for (key, value) in secret.iter_mut() {
+            self.apply_value(key, value, failures);
+        }
+    }
+
+    /// Applies the policy to `value`, found at `key` (a dotted path) in the secret.
+    fn apply_value(self, key: &str, value: &mut Value, failures: &mut Vec<KeyFailure>) {
+        let s = match &mut value.kind {
+            ValueKind::String(s) => s,
+            ValueKind::Table(table) => {
+                for (child, value) in table.iter_mut() {
+                    self.apply_value(&format!("{}.{}", key, child), value, failures);
+                }
+                return;
+            }
+            ValueKind::Array(items) => {
+                for (index, value) in items.iter_mut().enumerate() {
+                    self.apply_value(&format!("{}[{}]", key, index), value, failures);
+                }
+                return;
+            }
+            _ => return,
+        };
+        if !s.chars().any(is_unclean) {
+            return;
+        }
+
+        let cleaned = match self {
+            TextPolicy::Preserve => return,
+            TextPolicy::Error => {
+                failures.push(KeyFailure::key(
+                    key,
+                    "contains control characters or invalid UTF-8",
+                ));
+                return;
+            }
+            TextPolicy::Replace => s
+                .chars()
+                .map(|c| {
+                    if c.is_control() && is_unclean(c) {
+                        '\u{FFFD}'
+                    } else {
+                        c
+                    }
+                })
+                .collect(),
+            TextPolicy::Base64 => BASE64.encode(&*s),
+        };
+        *s = cleaned;
+    }
+}
+
+fn is_unclean(c: char) -> bool {
+    c == '\u{FFFD}' || (c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret() -> Map<String, Value> {
+        let mut db = Map::new();
+        db.insert("user".to_string(), Value::from("app\u{7}"));
+        db.insert("port".to_string(), Value::from(5432));
+
+        let mut secret = Map::new();
+        secret.insert("clean".to_string(), Value::from("line one\n\tline two"));
+        secret.insert("db".to_string(), Value::from(db));
+        secret.insert(
+            "hosts".to_string(),
+            Value::from(vec![Value::from("a"), Value::from("b\u{FFFD}")]),
+        );
+        secret
+    }
+
+    fn string(value: &Value) -> String {
+        value.clone().into_string().unwrap()
+    }
+
+    #[test]
+    fn parse_body_rejects_invalid_utf8_with_error() {
+        let body = b"{\"user\": \"\xff\"}";
+
+        let error = TextPolicy::Error.parse_body(body).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Vault response is not valid UTF-8"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn parse_body_decodes_invalid_utf8_lossily_otherwise() {
+        let body = b"{\"user\": \"\xff\"}";
+
+        for policy in [
+            TextPolicy::Preserve,
+            TextPolicy::Replace,
+            TextPolicy::Base64,
+        ] {
+            let json = policy.parse_body(body).unwrap();
+            assert_eq!(json["user"], "\u{FFFD}", "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn error_reports_every_nested_value() {
+        let mut secret = secret();
+        let mut failures = Vec::new();
+        TextPolicy::Error.apply(&mut secret, &mut failures);
+
+        failures.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(
+            failures,
+            vec![
+                KeyFailure::key("db.user", "contains control characters or invalid UTF-8"),
+                KeyFailure::key("hosts[1]", "contains control characters or invalid UTF-8"),
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_cleans_nested_and_array_values() {
+        let mut secret = secret();
+        let mut failures = Vec::new();
+        TextPolicy::Replace.apply(&mut secret, &mut failures);
+
+        assert!(failures.is_empty());
+        let db = secret["db"].clone().into_table().unwrap();
+        assert_eq!(string(&db["user"]), "app\u{FFFD}");
+        assert_eq!(db["port"].clone().into_int().unwrap(), 5432);
+        let hosts = secret["hosts"].clone().into_array().unwrap();
+        assert_eq!(string(&hosts[1]), "b\u{FFFD}");
+        assert_eq!(string(&secret["clean"]), "line one\n\tline two");
+    }
+
+    #[test]
+    fn base64_encodes_only_unclean_values() {
+        let mut secret = secret();
+        let mut failures = Vec::new();
+        TextPolicy::Base64.apply(&mut secret, &mut failures);
+
+        assert!(failures.is_empty());
+        let db = secret["db"].clone().into_table().unwrap();
+        assert_eq!(string(&db["user"]), BASE64.encode("app\u{7}"));
+        let hosts = secret["hosts"].clone().into_array().unwrap();
+        assert_eq!(string(&hosts[0]), "a");
+        assert_eq!(string(&hosts[1]), BASE64.encode("b\u{FFFD}"));
+        assert_eq!(string(&secret["clean"]), "line one\n\tline two");
+    }
+
+    #[test]
+    fn preserve_leaves_values_alone() {
+        let mut secret = secret();
+        let mut failures = Vec::new();
+        TextPolicy::Preserve.apply(&mut secret, &mut failures);
+
+        assert!(failures.is_empty());
+        let db = secret["db"].clone().into_table().unwrap();
+        assert_eq!(string(&db["user"]), "app\u{7}");
+    }
+}
@@ -0,0 +1,142 @@
+//! TLS settings of the HTTP clients used to talk to Vault.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use config::ConfigError;
+use reqwest::{Certificate, Identity};
+
+use crate::diagnostics;
+
+/// Where a trusted CA certificate is read from.
+#[derive(Debug, Clone)]
+pub(crate) enum CaSource {
+    /// PEM bundle given in memory.
+    Pem(Vec<u8>),
+    /// PEM bundle file, like `VAULT_CACERT`.
+    File(PathBuf),
+    /// Directory of PEM files, like `VAULT_CAPATH`.
+    Dir(PathBuf),
+}
+
+/// Where the client certificate presented to Vault is read from.
+#[derive(Clone)]
+pub(crate) enum ClientIdentity {
+    /// PEM certificate (chain) and PKCS#8 PEM private key given in memory.
+    Pem { cert: Vec<u8>, key: Vec<u8> },
+    /// PEM certificate and key files, like `VAULT_CLIENT_CERT` and `VAULT_CLIENT_KEY`.
+    Files { cert: PathBuf, key: PathBuf },
+    /// DER-encoded PKCS#12 archive.
+    #[cfg(feature = "native-tls")]
+    Pkcs12 { der: Vec<u8>, password: String },
+}
+
+impl ClientIdentity {
+    /// Loads the identity, reading files again so rotated certificates are picked up.
+    pub(crate) fn load(&self) -> Result<Identity, ConfigError> {
+        let identity = match self {
+            ClientIdentity::Pem { cert, key } => identity_from_pem(cert, key),
+            ClientIdentity::Files { cert, key } => {
+                let cert_pem = std::fs::read(cert).map_err(|e| identity_error(cert, e))?;
+                let key_pem = std::fs::read(key).map_err(|e| identity_error(key, e))?;
+                identity_from_pem(&cert_pem, &key_pem)
+            }
+            #[cfg(feature = "native-tls")]
+            ClientIdentity::Pkcs12 { der, password } => Identity::from_pkcs12_der(der, password),
+        };
+        identity.map_err(|e| {
+            ConfigError::Message(format!(
+                "Cannot load the TLS client certificate: {}",
+                diagnostics::root_cause(&e)
+            ))
+        })
+    }
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientIdentity::Pem { .. } => f.write_str("Pem(<redacted>)"),
+            ClientIdentity::Files { cert, key } => f
+                .debug_struct("Files")
+                .field("cert", cert)
+                .field("key", key)
+                .finish(),
+            #[cfg(feature = "native-tls")]
+            ClientIdentity::Pkcs12 { .. } => f.write_str("Pkcs12(<redacted>)"),
+        }
+    }
+}
+
+/// Creates an identity for the TLS backend in use from a PEM certificate and key.
+///
+/// reqwest uses native-tls when both backends are enabled, and each backend only
+/// accepts identities created by its own constructors.
+pub(crate) fn identity_from_pem(cert: &[u8], key: &[u8]) -> reqwest::Result<Identity> {
+    #[cfg(feature = "native-tls")]
+    let identity = Identity::from_pkcs8_pem(cert, key);
+    #[cfg(not(feature = "native-tls"))]
+    let identity = Identity::from_pem(&[cert, b"\n", key].concat());
+    identity
+}
+
+/// Loads the certificates of every CA source.
+///
+/// Files are read again for every client, so a rotated CA bundle is picked up by the
+/// next `collect`.
+pub(crate) fn load_ca_certificates(sources: &[CaSource]) -> Result<Vec<Certificate>, ConfigError> {
+    let mut certificates = Vec::new();
+    for source in sources {
+        match source {
+            CaSource::Pem(pem) => certificates.extend(parse_bundle(pem, "the PEM bundle")?),
+            CaSource::File(path) => certificates.extend(read_bundle(path)?),
+            CaSource::Dir(dir) => {
+                let entries = std::fs::read_dir(dir).map_err(|e| load_error(dir, e))?;
+                for entry in entries {
+                    let path = entry.map_err(|e| load_error(dir, e))?.path();
+                    if path.is_file() {
+                        certificates.extend(read_bundle(&path)?);
+                    }
+                }
+            }
+        }
+    }
+    Ok(certificates)
+}
+
+fn read_bundle(path: &Path) -> Result<Vec<Certificate>, ConfigError> {
+    let pem = std::fs::read(path).map_err(|e| load_error(path, e))?;
+    parse_bundle(&pem, &path.display().to_string())
+}
+
+fn parse_bundle(pem: &[u8], origin: &str) -> Result<Vec<Certificate>, ConfigError> {
+    let certificates = Certificate::from_pem_bundle(pem).map_err(|e| {
+        ConfigError::Message(format!(
+            "Cannot load CA certificates from {}: {}",
+            origin, e
+        ))
+    })?;
+    if certificates.is_empty() {
+        return Err(ConfigError::Message(format!(
+            "No PEM certificate found in {}",
+            origin
+        )));
+    }
+    Ok(certificates)
+}
+
+fn identity_error(path: &Path, error: std::io::Error) -> ConfigError {
+    ConfigError::Message(format!(
+        "Cannot load the TLS client certificate from {}: {}",
+        path.display(),
+        error
+    ))
+}
+
+fn load_error(path: &Path, error: std::io::Error) -> ConfigError {
+    ConfigError::Message(format!(
+        "Cannot load CA certificates from {}: {}",
+        path.display(),
+        error
+    ))
+}
@@ -0,0 +1,213 @@
+//! Child tokens scoped down from an authenticated identity.
+//!
+//! Creating tokens changes Vault state, so this module is compiled out by the
+//! `read-only` feature.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::ConfigError;
+use reqwest::Identity;
+use serde_json::{json, Value as JsonValue};
+
+use crate::auth::{AuthClient, AuthInfo, AuthMethod};
+use crate::VaultSource;
+
+/// Parameters of a child token created with `auth/token/create`.
+///
+/// With a `role`, the token is created through that token role, which lets Vault
+/// administrators bound the policies children may receive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChildTokenRequest {
+    /// Token role to create the token through.
+    pub role: Option<String>,
+    /// Policies of the child token; must be a subset of the parent's unless a role allows more.
+    pub policies: Vec<String>,
+    /// Time to live of the child token, sent in whole seconds rounded up.
+    pub ttl: Option<Duration>,
+    /// Maximum number of uses of the child token.
+    pub num_uses: Option<u64>,
+}
+
+impl ChildTokenRequest {
+    fn body(&self) -> JsonValue {
+        let mut body = json!({});
+        if !self.policies.is_empty() {
+            body["policies"] = json!(self.policies);
+        }
+        if let Some(ttl) = self.ttl {
+            // Vault takes whole seconds; rounding down would turn 500ms into "0s",
+            // which Vault reads as the default TTL.
+            let secs = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+            body["ttl"] = json!(format!("{}s", secs));
+        }
+        if let Some(num_uses) = self.num_uses {
+            body["num_uses"] = json!(num_uses);
+        }
+        body
+    }
+
+    fn path(&self) -> String {
+        match &self.role {
+            Some(role) => format!("v1/auth/token/create/{}", role),
+            None => "v1/auth/token/create".to_string(),
+        }
+    }
+}
+
+impl AuthClient<'_> {
+    /// Creates a child token of `parent_token` as described by `request`.
+    pub fn create_token(
+        &self,
+        parent_token: &str,
+        request: &ChildTokenRequest,
+    ) -> Result<AuthInfo, ConfigError> {
+        let url = self.source.build_api_url(&request.path())?;
+        let response = self.source.send(
+            self.client.post(url).json(&request.body()),
+            Some(parent_token),
+        )?;
+        AuthInfo::from_response(&response)
+    }
+}
+
+impl VaultSource {
+    /// Creates a child token of the source's own token, to hand to a subprocess or
+    /// plugin instead of sharing the parent token.
+    ///
+    /// The source authenticates as it would for `collect` (static token or auth
+    /// method), then calls `auth/token/create`, through the token role of `request`
+    /// if it has one. The child is revoked with its parent unless the role says
+    /// otherwise, so it cannot outlive the identity that minted it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// use config_vault::token::ChildTokenRequest;
+    /// use config_vault::VaultSource;
+    ///
+    /// let source = VaultSource::new(
+    ///     "http://127.0.0.1:8200",
+    ///     "hvs.EXAMPLE_TOKEN",
+    ///     "secret",
+    ///     "app",
+    /// );
+    ///
+    /// let child = source.create_child_token(&ChildTokenRequest {
+    ///     role: Some("report-generator".to_string()),
+    ///     ttl: Some(Duration::from_secs(600)),
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// Command::new("generate-report")
+    ///     .env("VAULT_TOKEN", &child.client_token)
+    ///     .status()
+    ///     .unwrap();
+    /// # Ok::<(), config::ConfigError>(())
+    /// ```
+    pub fn create_child_token(&self, request: &ChildTokenRequest) -> Result<AuthInfo, ConfigError> {
+        let client = self.http_client()?;
+        let token = self.resolve_token(&client)?;
+        AuthClient::new(self, &client).create_token(&token, request)
+    }
+}
+
+/// Logs in with a parent method, then scopes the token down to a child token.
+///
+/// Several sources can share one parent method while each receives a token holding
+/// only the policies it needs, so a compromise of one component does not expose
+/// every path the parent identity can read.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use config_vault::auth::{AppRoleAuth, AuthMethod};
+/// use config_vault::token::{ChildTokenRequest, ScopedTokenAuth};
+/// use config_vault::VaultSource;
+///
+/// let parent: Arc<dyn AuthMethod> = Arc::new(AppRoleAuth::new(
+///     "my-role-id",
+///     Some("my-secret-id".to_string()),
+/// ));
+///
+/// let database = VaultSource::builder()
+///     .addr("http://127.0.0.1:8200")
+///     .auth(Box::new(ScopedTokenAuth::new(
+///         parent.clone(),
+///         ChildTokenRequest {
+///             policies: vec!["read-database".to_string()],
+///             ..Default::default()
+///         },
+///     )))
+///     .mount("secret")
+///     .path("database")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScopedTokenAuth {
+    parent: Arc<dyn AuthMethod>,
+    request: ChildTokenRequest,
+}
+
+impl ScopedTokenAuth {
+    /// Logs in with `parent` and creates a child token described by `request`.
+    pub fn new(parent: Arc<dyn AuthMethod>, request: ChildTokenRequest) -> Self {
+        Self { parent, request }
+    }
+}
+
+impl AuthMethod for ScopedTokenAuth {
+    fn login(&self, client: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        let parent = self.parent.login(client)?;
+        client.create_token(&parent.client_token, &self.request)
+    }
+
+    fn identity(&self) -> Option<Identity> {
+        self.parent.identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ttl(ttl: Duration) -> JsonValue {
+        ChildTokenRequest {
+            ttl: Some(ttl),
+            ..Default::default()
+        }
+        .body()["ttl"]
+            .clone()
+    }
+
+    #[test]
+    fn ttl_is_rounded_up_to_whole_seconds() {
+        assert_eq!(ttl(Duration::from_secs(3600)), "3600s");
+        assert_eq!(ttl(Duration::from_millis(1500)), "2s");
+        assert_eq!(ttl(Duration::from_millis(500)), "1s");
+        assert_eq!(ttl(Duration::from_nanos(1)), "1s");
+    }
+
+    #[test]
+    fn body_lists_only_the_set_parameters() {
+        assert_eq!(ChildTokenRequest::default().body(), json!({}));
+
+        let request = ChildTokenRequest {
+            role: Some("billing".into()),
+            policies: vec!["read-database".into()],
+            ttl: Some(Duration::from_secs(60)),
+            num_uses: Some(3),
+        };
+        assert_eq!(
+            request.body(),
+            json!({ "policies": ["read-database"], "ttl": "60s", "num_uses": 3 })
+        );
+        assert_eq!(request.path(), "v1/auth/token/create/billing");
+    }
+}
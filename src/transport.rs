@@ -0,0 +1,230 @@
+//! A hook that intercepts the requests a source sends to Vault.
+//!
+//! A [`VaultSource`](crate::VaultSource) builds every request to Vault with reqwest.
+//! A [`VaultTransport`] set with
+//! [`VaultSource::set_transport`](crate::VaultSource::set_transport) intercepts the
+//! built requests (reads, logins and the other API calls of the source) and answers
+//! them in place of the reqwest client. Headers, namespaces, retries of throttled
+//! requests and response processing are unchanged. This is meant for test doubles
+//! that answer without a Vault server, and for recording or rewriting requests.
+//!
+//! It is not a replacement HTTP backend: reqwest and one of the TLS features are
+//! still required to build the crate, and the response is handed back as a reqwest
+//! response. Requests an auth method sends to other services, such as the cloud
+//! instance metadata endpoints, are not intercepted.
+
+use std::fmt;
+
+use config::ConfigError;
+use serde_json::Value as JsonValue;
+
+/// An HTTP request to Vault, handed to a [`VaultTransport`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct TransportRequest {
+    /// HTTP method, e.g. `"GET"`.
+    pub method: String,
+    /// Complete URL, e.g. "http://127.0.0.1:8200/v1/secret/data/dev".
+    pub url: String,
+    /// Headers in the order they are sent, including `X-Vault-Token`.
+    pub headers: Vec<(String, String)>,
+    /// Request body, for writes and logins.
+    pub body: Option<Vec<u8>>,
+}
+
+impl TransportRequest {
+    /// Returns the value of the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl fmt::Debug for TransportRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: Vec<(&str, &str)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                if name.eq_ignore_ascii_case("X-Vault-Token") {
+                    (name.as_str(), "<redacted>")
+                } else {
+                    (name.as_str(), value.as_str())
+                }
+            })
+            .collect();
+        f.debug_struct("TransportRequest")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &headers)
+            .field(
+                "body",
+                &self.body.as_ref().map(|b| format!("<{} bytes>", b.len())),
+            )
+            .finish()
+    }
+}
+
+/// The response of Vault to a [`TransportRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Response headers, e.g. `Retry-After` or `X-Vault-Index`.
+    pub headers: Vec<(String, String)>,
+    /// Response body.
+    pub body: Vec<u8>,
+}
+
+impl TransportResponse {
+    /// Creates a response without headers.
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Creates a response with a JSON body, as Vault sends.
+    pub fn json(status: u16, body: &JsonValue) -> Self {
+        let mut response = Self::new(status, body.to_string());
+        response
+            .headers
+            .push(("Content-Type".to_string(), "application/json".to_string()));
+        response
+    }
+
+    /// Converts the response into an `http::Response` that reqwest can wrap.
+    fn into_http(self) -> Result<http::Response<Vec<u8>>, ConfigError> {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        builder.body(self.body).map_err(|e| {
+            ConfigError::Message(format!("Invalid response from the Vault transport: {}", e))
+        })
+    }
+}
+
+/// Intercepts the requests of a [`VaultSource`](crate::VaultSource) to Vault.
+///
+/// Transports are blocking; the `AsyncSource` implementation calls them on tokio's
+/// blocking thread pool.
+///
+/// # Example
+///
+/// A test double that serves a fixed secret, so configuration loading can be tested
+/// without a Vault server:
+///
+/// ```
+/// use config::{Config, ConfigError};
+/// use config_vault::transport::{TransportRequest, TransportResponse, VaultTransport};
+/// use config_vault::VaultSource;
+/// use serde_json::json;
+///
+/// #[derive(Debug)]
+/// struct FixedSecret;
+///
+/// impl VaultTransport for FixedSecret {
+///     fn send(&self, request: &TransportRequest) -> Result<TransportResponse, ConfigError> {
+///         assert!(request.url.ends_with("/v1/secret/data/dev"));
+///         Ok(TransportResponse::json(
+///             200,
+///             &json!({ "data": { "data": { "db_user": "app" }, "metadata": { "version": 1 } } }),
+///         ))
+///     }
+/// }
+///
+/// let source = VaultSource::builder()
+///     .addr("http://vault.test:8200")
+///     .token("hvs.EXAMPLE_TOKEN")
+///     .mount("secret")
+///     .path("dev")
+///     .transport(Box::new(FixedSecret))
+///     .build()?;
+///
+/// # #[cfg(feature = "blocking")]
+/// # {
+/// let config = Config::builder().add_source(source).build()?;
+/// assert_eq!(config.get_string("db_user")?, "app");
+/// # }
+/// # Ok::<(), ConfigError>(())
+/// ```
+pub trait VaultTransport: fmt::Debug + Send + Sync {
+    /// Sends `request` and returns Vault's response, whatever its status. Errors are
+    /// for requests that got no response.
+    fn send(&self, request: &TransportRequest) -> Result<TransportResponse, ConfigError>;
+}
+
+/// Converts the parts of a built reqwest request into a [`TransportRequest`].
+fn transport_request(
+    method: &reqwest::Method,
+    url: &url::Url,
+    headers: &reqwest::header::HeaderMap,
+    body: Option<Option<&[u8]>>,
+) -> Result<TransportRequest, ConfigError> {
+    let body = match body {
+        None => None,
+        Some(Some(bytes)) => Some(bytes.to_vec()),
+        Some(None) => {
+            return Err(ConfigError::Message(
+                "Streaming request bodies cannot be sent through a Vault transport".into(),
+            ))
+        }
+    };
+    Ok(TransportRequest {
+        method: method.to_string(),
+        url: url.to_string(),
+        headers: headers
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect(),
+        body,
+    })
+}
+
+/// Sends a blocking reqwest request through `transport`.
+#[cfg(feature = "blocking")]
+pub(crate) fn send_blocking(
+    transport: &dyn VaultTransport,
+    request: reqwest::blocking::RequestBuilder,
+) -> Result<reqwest::blocking::Response, ConfigError> {
+    let request = request
+        .build()
+        .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+    let request = transport_request(
+        request.method(),
+        request.url(),
+        request.headers(),
+        request.body().map(|body| body.as_bytes()),
+    )?;
+    Ok(transport.send(&request)?.into_http()?.into())
+}
+
+/// Sends an async reqwest request through `transport` on the blocking thread pool.
+#[cfg(feature = "async")]
+pub(crate) async fn send_async(
+    transport: std::sync::Arc<dyn VaultTransport>,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, ConfigError> {
+    let request = request
+        .build()
+        .map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+    let request = transport_request(
+        request.method(),
+        request.url(),
+        request.headers(),
+        request.body().map(|body| body.as_bytes()),
+    )?;
+    let response = tokio::task::spawn_blocking(move || transport.send(&request))
+        .await
+        .map_err(|e| ConfigError::Foreign(Box::new(e)))??;
+    Ok(response.into_http()?.into())
+}
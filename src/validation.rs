@@ -0,0 +1,153 @@
+//! Validation failures of a loaded secret, reported together.
+//!
+//! Instead of stopping at the first problem, `collect` gathers every failure of the
+//! content of a secret into one [`ValidationError`], so a misconfigured secret can be
+//! fixed in one go. The failures cover:
+//!
+//! * keys that flattening, key renames or the key case map several keys to,
+//! * values rejected by the text policy or over the key and value size limits,
+//! * `{{ key }}` placeholders with unknown keys, cycles or no closing braces,
+//! * PEM bundles with a missing field,
+//! * computed keys whose closure failed,
+//! * missing required keys.
+//!
+//! Computed keys are user code that may rely on valid values, so they only run once
+//! every other check passed; a secret with other failures does not report theirs.
+//! Problems that are not about the content of the secret, such as an unreachable
+//! server, a denied token or a response without secret data, still fail on their own.
+
+use std::error::Error;
+use std::fmt;
+
+/// A failed check of one key of a secret, or of the whole secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyFailure {
+    /// The key, as a dotted path for nested values; `None` for the whole secret.
+    pub key: Option<String>,
+    /// Why the check failed, e.g. "is missing (required key)".
+    pub reason: String,
+}
+
+impl KeyFailure {
+    pub(crate) fn key(key: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            key: Some(key.into()),
+            reason: reason.into(),
+        }
+    }
+
+    pub(crate) fn secret(reason: impl Into<String>) -> Self {
+        Self {
+            key: None,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for KeyFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "'{}' {}", key, self.reason),
+            None => write!(f, "the secret {}", self.reason),
+        }
+    }
+}
+
+/// Every validation failure of a secret loaded by `collect`.
+///
+/// Returned inside `ConfigError::Foreign`; downcast it to list the failures:
+///
+/// ```
+/// use config::ConfigError;
+/// use config_vault::validation::ValidationError;
+///
+/// fn print_failures(error: &ConfigError) {
+///     if let ConfigError::Foreign(error) = error {
+///         if let Some(error) = error.downcast_ref::<ValidationError>() {
+///             for failure in error.failures() {
+///                 eprintln!("{}: {}", error.path(), failure);
+///             }
+///         }
+///     }
+/// }
+/// ```
+///
+/// A secret with a flattening collision, a broken placeholder and a missing required
+/// key reports all three:
+///
+/// ```
+/// use config::{ConfigError, Source};
+/// use config_vault::transport::{TransportRequest, TransportResponse, VaultTransport};
+/// use config_vault::validation::ValidationError;
+/// use config_vault::VaultSource;
+/// use serde_json::json;
+///
+/// #[derive(Debug)]
+/// struct FixedSecret;
+///
+/// impl VaultTransport for FixedSecret {
+///     fn send(&self, _: &TransportRequest) -> Result<TransportResponse, ConfigError> {
+///         let data = json!({
+///             "db.user": "a",
+///             "db": { "user": "b" },
+///             "url": "postgres://{{ host }}/app",
+///         });
+///         Ok(TransportResponse::json(200, &json!({ "data": { "data": data } })))
+///     }
+/// }
+///
+/// let source = VaultSource::builder()
+///     .addr("http://vault.test:8200")
+///     .token("hvs.EXAMPLE_TOKEN")
+///     .mount("secret")
+///     .path("dev")
+///     .flatten_separator(".")
+///     .interpolation(true)
+///     .required_key("password")
+///     .transport(Box::new(FixedSecret))
+///     .build()?;
+///
+/// # #[cfg(feature = "blocking")]
+/// # {
+/// let Err(ConfigError::Foreign(error)) = source.collect() else {
+///     panic!("the secret is invalid");
+/// };
+/// let error = error.downcast_ref::<ValidationError>().unwrap();
+/// let keys: Vec<_> = error.failures().iter().map(|f| f.key.as_deref()).collect();
+/// assert_eq!(keys, [Some("db.user"), Some("url"), Some("password")]);
+/// # }
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    path: String,
+    failures: Vec<KeyFailure>,
+}
+
+impl ValidationError {
+    pub(crate) fn new(path: String, failures: Vec<KeyFailure>) -> Self {
+        Self { path, failures }
+    }
+
+    /// The path of the secret, e.g. "secret/app/prod".
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The failures, in the order they were found.
+    pub fn failures(&self) -> &[KeyFailure] {
+        &self.failures
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Vault secret {} failed validation:", self.path)?;
+        for failure in &self.failures {
+            write!(f, "\n  - {}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ValidationError {}
@@ -0,0 +1,99 @@
+//! A Vault double shared by the tests that load secrets through `collect`.
+
+#![allow(dead_code)]
+
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use config::ConfigError;
+use config_vault::transport::{TransportRequest, TransportResponse, VaultTransport};
+use config_vault::validation::{KeyFailure, ValidationError};
+use config_vault::{VaultSource, VaultSourceBuilder};
+use serde_json::{json, Value as JsonValue};
+
+type Respond = dyn Fn(&TransportRequest) -> TransportResponse + Send + Sync;
+
+/// Answers requests with a closure and records them.
+#[derive(Clone)]
+pub struct MockVault {
+    respond: Arc<Respond>,
+    requests: Arc<Mutex<Vec<TransportRequest>>>,
+}
+
+impl MockVault {
+    pub fn new<F>(respond: F) -> Self
+    where
+        F: Fn(&TransportRequest) -> TransportResponse + Send + Sync + 'static,
+    {
+        Self {
+            respond: Arc::new(respond),
+            requests: Arc::default(),
+        }
+    }
+
+    /// Answers every request with a KV v2 read of `data`.
+    pub fn secret(data: JsonValue) -> Self {
+        let response = kv2_read(data);
+        Self::new(move |_| TransportResponse::json(200, &response))
+    }
+
+    /// The requests received so far.
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// A builder for `secret/app` at a fake address that sends its requests here.
+    pub fn source(&self) -> VaultSourceBuilder {
+        VaultSource::builder()
+            .addr("http://vault.test:8200")
+            .token("hvs.EXAMPLE_TOKEN")
+            .mount("secret")
+            .path("app")
+            .http_client(http_client())
+            .transport(Box::new(self.clone()))
+    }
+}
+
+impl fmt::Debug for MockVault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockVault").finish_non_exhaustive()
+    }
+}
+
+impl VaultTransport for MockVault {
+    fn send(&self, request: &TransportRequest) -> Result<TransportResponse, ConfigError> {
+        self.requests.lock().unwrap().push(request.clone());
+        Ok((self.respond)(request))
+    }
+}
+
+/// The body of a KV v2 read of `data`.
+pub fn kv2_read(data: JsonValue) -> JsonValue {
+    json!({ "data": { "data": data, "metadata": { "version": 1 } } })
+}
+
+/// Requests are still built with a reqwest client; sharing one avoids loading the
+/// platform certificates for every source with `native-tls`.
+pub fn http_client() -> reqwest::blocking::Client {
+    static CLIENT: OnceLock<reqwest::blocking::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::blocking::Client::new).clone()
+}
+
+/// The failures of a `collect` error, which must be a `ValidationError`.
+pub fn failures(error: ConfigError) -> Vec<KeyFailure> {
+    match error {
+        ConfigError::Foreign(error) => match error.downcast_ref::<ValidationError>() {
+            Some(error) => error.failures().to_vec(),
+            None => panic!("not a validation error: {}", error),
+        },
+        error => panic!("not a validation error: {}", error),
+    }
+}
+
+/// Shorthand for a failure of `key`.
+pub fn failure(key: &str, reason: &str) -> KeyFailure {
+    KeyFailure {
+        key: Some(key.to_string()),
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,78 @@
+//! Keys computed from the fetched values during `collect`.
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use common::{failure, failures, MockVault};
+use config::{ConfigError, Source, Value};
+use serde_json::json;
+
+#[test]
+fn computed_keys_are_added_in_registration_order() {
+    let vault = MockVault::secret(json!({ "user": "app", "host": "db.internal" }));
+    let mut source = vault.source().build().unwrap();
+    source.add_computed_key("dsn", |values| {
+        Ok(Value::from(format!(
+            "postgres://{}@{}/app",
+            values["user"].clone().into_string()?,
+            values["host"].clone().into_string()?
+        )))
+    });
+    source.add_computed_key("dsn_length", |values| {
+        Ok(Value::from(
+            values["dsn"].clone().into_string()?.len() as i64
+        ))
+    });
+
+    let values = source.collect().unwrap();
+    assert_eq!(
+        values["dsn"].clone().into_string().unwrap(),
+        "postgres://app@db.internal/app"
+    );
+    assert_eq!(values["dsn_length"].clone().into_int().unwrap(), 30);
+    assert_eq!(values["user"].clone().into_string().unwrap(), "app");
+}
+
+#[test]
+fn closure_errors_are_key_failures() {
+    let vault = MockVault::secret(json!({ "user": "app" }));
+    let mut source = vault.source().build().unwrap();
+    source.add_computed_key("dsn", |_| {
+        Err(ConfigError::Message("the password is not set".into()))
+    });
+
+    let error = source.collect().unwrap_err();
+    assert_eq!(
+        failures(error),
+        vec![failure(
+            "dsn",
+            "cannot be computed: the password is not set"
+        )]
+    );
+}
+
+#[test]
+fn computed_keys_are_skipped_when_other_checks_fail() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let vault = MockVault::secret(json!({ "url": "postgres://{{ user }}@db" }));
+    let mut source = vault.source().build().unwrap();
+    source.set_interpolation(true);
+    source.add_required_key("dsn");
+    let counter = Arc::clone(&calls);
+    source.add_computed_key("dsn", move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(Value::from("unused"))
+    });
+
+    let error = source.collect().unwrap_err();
+    // The required computed key is not reported as missing on top of the failure.
+    assert_eq!(
+        failures(error),
+        vec![failure("url", "references the unknown template key 'user'")]
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,98 @@
+//! `<key>__expires_at` companion keys of secrets read with a lease.
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::{kv2_read, MockVault};
+use config::{ConfigError, Source};
+use config_vault::transport::TransportResponse;
+use serde_json::json;
+
+/// Dynamic database credentials with a one hour lease.
+fn database_creds() -> MockVault {
+    MockVault::new(|_| {
+        TransportResponse::json(
+            200,
+            &json!({
+                "lease_id": "database/creds/app/abcd",
+                "lease_duration": 3600,
+                "renewable": true,
+                "data": { "username": "v-app-1234", "password": "s3cr3t" }
+            }),
+        )
+    })
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[test]
+fn leased_values_get_companion_keys() {
+    let vault = database_creds();
+    let source = vault
+        .source()
+        .custom_endpoint("v1/database/creds/app", |response| {
+            response["data"]
+                .as_object()
+                .cloned()
+                .ok_or_else(|| ConfigError::Message("no credentials".into()))
+        })
+        .expiry_keys(true)
+        .build()
+        .unwrap();
+
+    let before = unix_now();
+    let values = source.collect().unwrap();
+    let after = unix_now();
+
+    assert_eq!(values.len(), 4);
+    for key in ["username", "password"] {
+        let expires_at = values[&format!("{}__expires_at", key)]
+            .clone()
+            .into_int()
+            .unwrap();
+        assert!(
+            (before + 3600..=after + 3600).contains(&expires_at),
+            "{}: {}",
+            key,
+            expires_at
+        );
+    }
+}
+
+#[test]
+fn companion_keys_are_opt_in() {
+    let vault = database_creds();
+    let source = vault
+        .source()
+        .custom_endpoint("v1/database/creds/app", |response| {
+            Ok(response["data"].as_object().cloned().unwrap_or_default())
+        })
+        .build()
+        .unwrap();
+
+    let values = source.collect().unwrap();
+    assert_eq!(values.len(), 2);
+    assert!(!values.contains_key("username__expires_at"));
+}
+
+#[test]
+fn secrets_without_a_lease_get_no_companion_keys() {
+    let vault = MockVault::new(|_| {
+        let mut read = kv2_read(json!({ "user": "app" }));
+        read["lease_duration"] = json!(0);
+        TransportResponse::json(200, &read)
+    });
+    let source = vault.source().expiry_keys(true).build().unwrap();
+
+    let values = source.collect().unwrap();
+    assert_eq!(values.len(), 1);
+    assert!(values.contains_key("user"));
+}
@@ -0,0 +1,73 @@
+//! Secrets loaded as a single key holding serialized JSON.
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::MockVault;
+use config::{Source, Value};
+use serde_json::{json, Value as JsonValue};
+
+fn blob(value: &Value) -> JsonValue {
+    serde_json::from_str(&value.clone().into_string().unwrap()).unwrap()
+}
+
+#[test]
+fn the_secret_is_loaded_as_one_json_value() {
+    let data =
+        json!({ "user": "app", "port": 5432, "hosts": ["a", "b"], "tls": { "enabled": true } });
+    let vault = MockVault::secret(data.clone());
+    let source = vault.source().json_key("credentials").build().unwrap();
+
+    let values = source.collect().unwrap();
+    assert_eq!(values.len(), 1);
+    assert_eq!(blob(&values["credentials"]), data);
+}
+
+#[test]
+fn placeholders_are_passed_through_untouched() {
+    let data = json!({ "user": "app", "url": "postgres://{{ user }}@db" });
+    let vault = MockVault::secret(data.clone());
+    let mut source = vault.source().json_key("credentials").build().unwrap();
+    source.set_interpolation(true);
+
+    let values = source.collect().unwrap();
+    assert_eq!(blob(&values["credentials"]), data);
+}
+
+#[test]
+fn computed_keys_and_pem_bundles_are_added_next_to_it() {
+    let vault = MockVault::secret(json!({ "cert": "-----BEGIN CERTIFICATE-----" }));
+    let mut source = vault
+        .source()
+        .json_key("credentials")
+        .computed_key("format", |_| Ok(Value::from("json")))
+        .build()
+        .unwrap();
+    source.add_pem_bundle("bundle", ["cert"]);
+
+    let values = source.collect().unwrap();
+    let mut keys: Vec<&String> = values.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["bundle", "credentials", "format"]);
+    assert_eq!(
+        values["bundle"].clone().into_string().unwrap(),
+        "-----BEGIN CERTIFICATE-----\n"
+    );
+}
+
+#[test]
+fn flattening_is_rejected_along_with_it() {
+    let vault = MockVault::secret(json!({}));
+    let error = vault
+        .source()
+        .json_key("credentials")
+        .flatten_separator("__")
+        .build()
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "VaultSource flatten separator and JSON key are mutually exclusive"
+    );
+}
@@ -0,0 +1,57 @@
+//! Limits on the number of keys and the size of the values of a secret.
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{failure, failures, MockVault};
+use config::Source;
+use config_vault::validation::KeyFailure;
+use serde_json::json;
+
+#[test]
+fn secrets_within_the_limits_load() {
+    let vault = MockVault::secret(json!({ "user": "app", "pass": "1234" }));
+    let mut source = vault.source().build().unwrap();
+    source.set_max_keys(2);
+    source.set_max_value_size(4);
+
+    assert_eq!(source.collect().unwrap().len(), 2);
+}
+
+#[test]
+fn too_many_keys_fail_the_whole_secret() {
+    let vault = MockVault::secret(json!({ "a": 1, "b": 2, "c": 3 }));
+    let mut source = vault.source().build().unwrap();
+    source.set_max_keys(2);
+
+    let error = source.collect().unwrap_err();
+    assert_eq!(
+        failures(error),
+        vec![KeyFailure {
+            key: None,
+            reason: "has 3 keys, more than the limit of 2".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn oversized_values_are_reported_by_path() {
+    let vault = MockVault::secret(json!({
+        "user": "app",
+        "db": { "password": "s3cr3t" },
+        "hosts": ["a", "db.internal"]
+    }));
+    let mut source = vault.source().build().unwrap();
+    source.set_max_value_size(4);
+
+    let mut failures = failures(source.collect().unwrap_err());
+    failures.sort_by(|a, b| a.key.cmp(&b.key));
+    assert_eq!(
+        failures,
+        vec![
+            failure("db.password", "is 6 bytes, more than the limit of 4"),
+            failure("hosts[1]", "is 11 bytes, more than the limit of 4"),
+        ]
+    );
+}
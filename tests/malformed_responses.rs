@@ -0,0 +1,286 @@
+//! Malformed, truncated and non-UTF-8 Vault responses make `collect` fail with an
+//! error, never panic, whatever processing options are enabled.
+
+#![cfg(feature = "blocking")]
+
+use std::sync::OnceLock;
+
+use config::{ConfigError, Source, Value};
+use config_vault::transport::{TransportRequest, TransportResponse, VaultTransport};
+use config_vault::{EngineKind, KeyCase, TextPolicy, VaultSource};
+
+/// Answers every request with the same status and body.
+#[derive(Debug)]
+struct FixedBody {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl VaultTransport for FixedBody {
+    fn send(&self, _: &TransportRequest) -> Result<TransportResponse, ConfigError> {
+        Ok(TransportResponse::new(self.status, self.body.clone()))
+    }
+}
+
+type Options = fn(&mut VaultSource);
+
+/// Processing options that change how the body of a KV v2 read is handled.
+const OPTION_SETS: &[(&str, Options)] = &[
+    ("defaults", |_| {}),
+    ("flatten", |s| s.set_flatten_separator(Some(".".into()))),
+    ("flatten with key case", |s| {
+        s.set_flatten_separator(Some("__".into()));
+        s.set_key_case(KeyCase::Upper);
+    }),
+    ("interpolation", |s| {
+        s.set_interpolation(true);
+        s.add_required_key("url");
+    }),
+    ("json key", |s| s.set_json_key(Some("blob".into()))),
+    ("nested with expiry keys", |s| {
+        s.set_nested_at(Some("app".into()));
+        s.set_expiry_keys(true);
+    }),
+    ("strict text", |s| s.set_text_policy(TextPolicy::Error)),
+    ("base64 text with limits", |s| {
+        s.set_text_policy(TextPolicy::Base64);
+        s.set_max_keys(2);
+        s.set_max_value_size(4);
+    }),
+    ("filters and renames", |s| {
+        s.add_include_key("*");
+        s.add_exclude_key("secret_*");
+        s.add_key_rename("user", "username");
+        s.set_key_case(KeyCase::Kebab);
+    }),
+    ("pem and computed keys", |s| {
+        s.add_pem_bundle("bundle", vec!["cert".to_string(), "ca".to_string()]);
+        s.add_computed_key("count", |values| Ok(Value::from(values.len() as i64)));
+    }),
+];
+
+/// A well-formed KV v2 read that exercises every option above.
+const VALID_BODY: &str = r#"{
+    "lease_duration": 3600,
+    "data": {
+        "data": {
+            "user": "app",
+            "host": "db.internal",
+            "url": "postgres://{{ user }}@{{ host }}/app",
+            "cert": "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----",
+            "ca": "-----BEGIN CERTIFICATE-----\nMIIC\n-----END CERTIFICATE-----",
+            "pool": { "max": 5, "hosts": ["a", "b"] },
+            "ratio": 0.5,
+            "enabled": true,
+            "missing": null
+        },
+        "metadata": { "version": 3 }
+    }
+}"#;
+
+/// Requests are still built with a reqwest client; sharing one avoids loading the
+/// platform certificates for every source with `native-tls`.
+fn http_client() -> reqwest::blocking::Client {
+    static CLIENT: OnceLock<reqwest::blocking::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::blocking::Client::new).clone()
+}
+
+fn collect(options: Options, status: u16, body: &[u8]) -> Result<(), ConfigError> {
+    let mut source = VaultSource::builder()
+        .addr("http://vault.test:8200")
+        .token("hvs.EXAMPLE_TOKEN")
+        .mount("secret")
+        .path("app")
+        .http_client(http_client())
+        .transport(Box::new(FixedBody {
+            status,
+            body: body.to_vec(),
+        }))
+        .build()?;
+    options(&mut source);
+    source.collect().map(|_| ())
+}
+
+/// Bodies that are not a KV v2 read response.
+fn malformed_bodies() -> Vec<Vec<u8>> {
+    let mut bodies: Vec<Vec<u8>> = [
+        "",
+        " ",
+        "{",
+        "}",
+        "not json",
+        "null",
+        "42",
+        "[]",
+        r#""data""#,
+        "{}",
+        r#"{"data": null}"#,
+        r#"{"data": []}"#,
+        r#"{"data": "secret"}"#,
+        r#"{"data": {}}"#,
+        r#"{"data": {"data": null}}"#,
+        r#"{"data": {"data": [1, 2]}}"#,
+        r#"{"data": {"data": "secret"}}"#,
+        r#"{"data": {"data": {"user": "app"}}"#,
+        r#"{"data": {"data": {"user": "app"}}} trailing"#,
+        r#"{"data": {"data": {"user": "\ud800"}}}"#,
+        r#"{"data": {"data": {"user": "app\"}}}"#,
+    ]
+    .iter()
+    .map(|body| body.as_bytes().to_vec())
+    .collect();
+
+    // Out of the range of f64, unless numbers are kept as written.
+    if !cfg!(feature = "arbitrary-precision") {
+        bodies.push(br#"{"data": {"data": {"n": 1e400}}}"#.to_vec());
+    }
+    // Deeper than the recursion limit of the JSON parser.
+    bodies.push(format!("{}{}", "[".repeat(10_000), "]".repeat(10_000)).into_bytes());
+    bodies.push(
+        format!(
+            r#"{{"data": {{"data": {}1{}}}}}"#,
+            r#"{"a": "#.repeat(10_000),
+            "}".repeat(10_000)
+        )
+        .into_bytes(),
+    );
+    bodies.push(vec![0xff, 0xfe, 0x00]);
+    bodies.push(vec![0; 64]);
+    bodies
+}
+
+/// KV v2 read responses with bytes that are not valid UTF-8.
+fn non_utf8_bodies() -> Vec<Vec<u8>> {
+    let mut bodies = vec![
+        b"{\"data\": {\"data\": {\"user\": \"\xff\xfe\"}}}".to_vec(),
+        b"{\"data\": {\"data\": {\"\xc3\": \"app\"}}}".to_vec(),
+        b"{\"data\": {\"data\": {\"url\": \"{{ \xed\xa0\x80 }}\"}}}".to_vec(),
+    ];
+    let mut truncated_char = VALID_BODY.replace("app", "contraseña").into_bytes();
+    let split = truncated_char
+        .windows(2)
+        .position(|w| w == "ñ".as_bytes())
+        .unwrap();
+    truncated_char.remove(split + 1);
+    bodies.push(truncated_char);
+    bodies
+}
+
+/// Deterministic xorshift generator, so failures are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[test]
+fn valid_body_loads_with_every_option_set() {
+    for (name, options) in OPTION_SETS {
+        let result = collect(*options, 200, VALID_BODY.as_bytes());
+        // The limits are there to fail on this secret.
+        if *name == "base64 text with limits" {
+            assert!(result.is_err(), "{}", name);
+        } else {
+            assert!(result.is_ok(), "{}: {:?}", name, result);
+        }
+    }
+}
+
+#[test]
+fn malformed_bodies_fail() {
+    for body in malformed_bodies() {
+        for (name, options) in OPTION_SETS {
+            for status in [200, 403, 404, 500] {
+                let result = collect(*options, status, &body);
+                assert!(
+                    result.is_err(),
+                    "{} with status {} loaded {:?}",
+                    name,
+                    status,
+                    String::from_utf8_lossy(&body)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn non_utf8_bodies_fail_with_strict_text_and_never_panic() {
+    for body in non_utf8_bodies() {
+        assert!(std::str::from_utf8(&body).is_err());
+        for (_, options) in OPTION_SETS {
+            // Decoded lossily; whether the result loads depends on the options.
+            let _ = collect(*options, 200, &body);
+        }
+
+        let error = collect(|s| s.set_text_policy(TextPolicy::Error), 200, &body).unwrap_err();
+        assert!(error.to_string().contains("not valid UTF-8"), "{}", error);
+    }
+}
+
+#[test]
+fn truncated_bodies_fail() {
+    let body = VALID_BODY.as_bytes();
+    for end in 0..body.len() {
+        // Every strict prefix of a JSON object is incomplete, whitespace aside.
+        if body[end..].iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        for (name, options) in OPTION_SETS {
+            assert!(
+                collect(*options, 200, &body[..end]).is_err(),
+                "{} loaded a body truncated at {}",
+                name,
+                end
+            );
+        }
+    }
+}
+
+#[test]
+fn mutated_bodies_never_panic() {
+    const SYNTAX: &[u8] = b"{}[]\":,\\\xff";
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let engines = [EngineKind::Kv2, EngineKind::Kv1];
+    for _ in 0..2_000 {
+        let mut body = VALID_BODY.as_bytes().to_vec();
+        for _ in 0..1 + rng.below(4) {
+            let at = rng.below(body.len());
+            match rng.below(4) {
+                0 => body[at] = rng.next() as u8,
+                1 => {
+                    body.remove(at);
+                }
+                2 => body.insert(at, SYNTAX[rng.below(SYNTAX.len())]),
+                _ => body.truncate(at),
+            }
+            if body.is_empty() {
+                break;
+            }
+        }
+
+        let (_, options) = OPTION_SETS[rng.below(OPTION_SETS.len())];
+        let engine = engines[rng.below(engines.len())].clone();
+        let mut source = VaultSource::builder()
+            .addr("http://vault.test:8200")
+            .token("hvs.EXAMPLE_TOKEN")
+            .mount("secret")
+            .path("app")
+            .engine(engine)
+            .http_client(http_client())
+            .transport(Box::new(FixedBody { status: 200, body }))
+            .build()
+            .unwrap();
+        options(&mut source);
+        let _ = source.collect();
+    }
+}
@@ -0,0 +1,65 @@
+//! Secrets loaded under a parent key instead of the configuration root.
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{failure, failures, MockVault};
+use config::{Config, Source};
+use serde_json::json;
+
+#[test]
+fn keys_are_loaded_under_the_parent() {
+    let vault = MockVault::secret(json!({ "user": "app", "pool": { "max": 5 } }));
+    let source = vault.source().nested_at("database").build().unwrap();
+
+    let config = Config::builder().add_source(source).build().unwrap();
+    assert_eq!(config.get_string("database.user").unwrap(), "app");
+    assert_eq!(config.get_int("database.pool.max").unwrap(), 5);
+    assert!(config.get_string("user").is_err());
+}
+
+#[test]
+fn dotted_parents_nest_several_levels() {
+    let vault = MockVault::secret(json!({ "user": "app" }));
+    let source = vault
+        .source()
+        .nested_at("services.billing")
+        .build()
+        .unwrap();
+
+    let values = source.collect().unwrap();
+    assert_eq!(values.len(), 1);
+    let config = Config::builder().add_source(source).build().unwrap();
+    assert_eq!(config.get_string("services.billing.user").unwrap(), "app");
+}
+
+#[test]
+fn required_keys_name_keys_of_the_secret() {
+    let vault = MockVault::secret(json!({ "user": "app" }));
+    let source = vault
+        .source()
+        .nested_at("database")
+        .required_key("user")
+        .required_key("password")
+        .build()
+        .unwrap();
+
+    let error = source.collect().unwrap_err();
+    assert_eq!(
+        failures(error),
+        vec![failure("password", "is missing (required key)")]
+    );
+}
+
+#[test]
+fn parents_with_an_empty_segment_are_rejected() {
+    let vault = MockVault::secret(json!({}));
+    for parent in ["", ".database", "database.", "services..billing"] {
+        let error = vault.source().nested_at(parent).build().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("VaultSource parent key '{}' has an empty segment", parent)
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! PEM bundles assembled from several fields of a secret.
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{failure, failures, MockVault};
+use config::Source;
+use serde_json::json;
+
+const CERT: &str = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----";
+const CA: &str = "-----BEGIN CERTIFICATE-----\nMIIC\n-----END CERTIFICATE-----";
+
+#[test]
+fn fields_are_concatenated_in_order() {
+    let vault = MockVault::secret(json!({ "certificate": format!("{}\n\n", CERT), "ca": CA }));
+    let mut source = vault.source().build().unwrap();
+    source.add_pem_bundle("fullchain", ["certificate", "ca"]);
+
+    let values = source.collect().unwrap();
+    assert_eq!(
+        values["fullchain"].clone().into_string().unwrap(),
+        format!("{}\n{}\n", CERT, CA)
+    );
+    assert!(values.contains_key("certificate"));
+    assert!(values.contains_key("ca"));
+}
+
+#[test]
+fn missing_and_non_string_fields_are_reported() {
+    let vault = MockVault::secret(json!({ "certificate": CERT, "serial": 42 }));
+    let mut source = vault.source().build().unwrap();
+    source.add_pem_bundle("fullchain", ["certificate", "ca"]);
+    source.add_pem_bundle("numbered", ["serial"]);
+
+    let error = source.collect().unwrap_err();
+    assert_eq!(
+        failures(error),
+        vec![
+            failure(
+                "fullchain",
+                "cannot be assembled: PEM field 'ca' is missing or not a string"
+            ),
+            failure(
+                "numbered",
+                "cannot be assembled: PEM field 'serial' is missing or not a string"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn fields_are_read_under_their_vault_names() {
+    let vault = MockVault::secret(json!({ "certificate": CERT, "ca": CA, "user": "app" }));
+    let mut source = vault.source().build().unwrap();
+    source.add_key_rename("certificate", "tls.cert");
+    source.add_exclude_key("ca");
+    source.add_pem_bundle("fullchain", ["certificate", "ca"]);
+
+    let values = source.collect().unwrap();
+    assert_eq!(
+        values["fullchain"].clone().into_string().unwrap(),
+        format!("{}\n{}\n", CERT, CA)
+    );
+    assert!(!values.contains_key("ca"));
+    assert!(!values.contains_key("certificate"));
+}
@@ -0,0 +1,108 @@
+//! Origins of the values loaded from Vault, as reported by `provenance`.
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::MockVault;
+use config::Config;
+use config_vault::{provenance, KeyCase};
+use serde_json::json;
+
+fn load(vault: &MockVault, configure: impl FnOnce(&mut config_vault::VaultSource)) -> Config {
+    let mut source = vault.source().build().unwrap();
+    configure(&mut source);
+    Config::builder().add_source(source).build().unwrap()
+}
+
+#[test]
+fn origins_name_the_vault_keys() {
+    let vault = MockVault::secret(json!({ "user": "app", "pool": { "hosts": ["a", "b"] } }));
+    let config = load(&vault, |_| {});
+
+    assert_eq!(
+        provenance(&config, "user").as_deref(),
+        Some("vault://secret/app#user")
+    );
+    assert_eq!(
+        provenance(&config, "pool.hosts[1]").as_deref(),
+        Some("vault://secret/app#pool.hosts[1]")
+    );
+}
+
+#[test]
+fn renamed_keys_keep_the_origin_of_the_vault_key() {
+    let vault = MockVault::secret(json!({ "DB_PASS": "s3cr3t", "user": "app" }));
+    let config = load(&vault, |source| {
+        source.add_key_rename("DB_PASS", "database.password");
+    });
+
+    assert_eq!(config.get_string("database.password").unwrap(), "s3cr3t");
+    assert_eq!(
+        provenance(&config, "database.password").as_deref(),
+        Some("vault://secret/app#DB_PASS")
+    );
+    assert_eq!(
+        provenance(&config, "user").as_deref(),
+        Some("vault://secret/app#user")
+    );
+}
+
+#[test]
+fn flat_keys_report_their_path_in_the_secret() {
+    let vault = MockVault::secret(json!({ "db": { "user": "app" } }));
+    let config = load(&vault, |source| {
+        source.set_flatten_separator(Some("__".into()));
+        source.add_key_rename("db__user", "username");
+    });
+
+    assert_eq!(
+        provenance(&config, "username").as_deref(),
+        Some("vault://secret/app#db.user")
+    );
+}
+
+#[test]
+fn interpolated_values_keep_their_origin() {
+    let vault = MockVault::secret(json!({ "host": "db", "URL": "postgres://{{ host }}/app" }));
+    let config = load(&vault, |source| {
+        source.set_interpolation(true);
+        source.add_key_rename("URL", "url");
+    });
+
+    assert_eq!(config.get_string("url").unwrap(), "postgres://db/app");
+    assert_eq!(
+        provenance(&config, "url").as_deref(),
+        Some("vault://secret/app#URL")
+    );
+}
+
+#[test]
+fn added_keys_report_their_own_name() {
+    let vault = MockVault::secret(json!({ "user": "app" }));
+    let config = load(&vault, |source| {
+        source.add_computed_key("greeting", |values| {
+            Ok(format!("hello {}", values["user"].clone().into_string()?).into())
+        });
+    });
+
+    assert_eq!(
+        provenance(&config, "greeting").as_deref(),
+        Some("vault://secret/app#greeting")
+    );
+}
+
+#[test]
+fn key_case_keeps_the_origin_of_the_vault_key() {
+    let vault = MockVault::secret(json!({ "DB_PASS": "s3cr3t", "Pool": { "Max": 5 } }));
+    let config = load(&vault, |source| source.set_key_case(KeyCase::Lower));
+
+    assert_eq!(
+        provenance(&config, "db_pass").as_deref(),
+        Some("vault://secret/app#DB_PASS")
+    );
+    assert_eq!(
+        provenance(&config, "pool.max").as_deref(),
+        Some("vault://secret/app#Pool.Max")
+    );
+}
@@ -0,0 +1,89 @@
+//! Logins reused across `collect` calls and clones of a source.
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use common::{kv2_read, MockVault};
+use config::{ConfigError, Source};
+use config_vault::auth::{AuthClient, AuthInfo, AuthMethod};
+use config_vault::transport::TransportResponse;
+use serde_json::json;
+
+/// Hands out a new token, `hvs.login-<n>`, on every login.
+#[derive(Debug, Clone, Default)]
+struct CountingAuth(Arc<AtomicUsize>);
+
+impl CountingAuth {
+    fn logins(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl AuthMethod for CountingAuth {
+    fn login(&self, _: &AuthClient<'_>) -> Result<AuthInfo, ConfigError> {
+        let n = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(AuthInfo::from_token(format!("hvs.login-{}", n)))
+    }
+}
+
+#[test]
+fn logins_are_reused_across_collects_and_clones() {
+    let auth = CountingAuth::default();
+    let vault = MockVault::secret(json!({ "user": "app" }));
+    let source = vault
+        .source()
+        .auth(Box::new(auth.clone()))
+        .reuse_login(true)
+        .build()
+        .unwrap();
+
+    source.collect().unwrap();
+    source.collect().unwrap();
+    source.clone().collect().unwrap();
+
+    assert_eq!(auth.logins(), 1);
+    assert!(vault
+        .requests()
+        .iter()
+        .all(|request| request.header("X-Vault-Token") == Some("hvs.login-1")));
+}
+
+#[test]
+fn rejected_tokens_are_dropped() {
+    let auth = CountingAuth::default();
+    let vault = MockVault::new(|request| {
+        if request.header("X-Vault-Token") == Some("hvs.login-1") {
+            TransportResponse::json(403, &json!({ "errors": ["permission denied"] }))
+        } else {
+            TransportResponse::json(200, &kv2_read(json!({ "user": "app" })))
+        }
+    });
+    let source = vault
+        .source()
+        .auth(Box::new(auth.clone()))
+        .reuse_login(true)
+        .build()
+        .unwrap();
+
+    source.collect().unwrap_err();
+    source.collect().unwrap();
+    source.collect().unwrap();
+
+    assert_eq!(auth.logins(), 2);
+}
+
+#[test]
+fn logins_are_not_reused_by_default() {
+    let auth = CountingAuth::default();
+    let vault = MockVault::secret(json!({ "user": "app" }));
+    let source = vault.source().auth(Box::new(auth.clone())).build().unwrap();
+
+    source.collect().unwrap();
+    source.collect().unwrap();
+
+    assert_eq!(auth.logins(), 2);
+}